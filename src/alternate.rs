@@ -1,30 +1,408 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
 #[cfg(windows)]
 use crossterm_utils::supports_ansi;
 use crossterm_utils::Result;
+use lazy_static::lazy_static;
+
+use crate::{dumb, restore};
 
-pub(crate) use ansi::AnsiAlternateScreen;
+pub(crate) use ansi::{AnsiAlternateScreen, WriterAlternateScreen};
+pub use builder::AlternateScreenBuilder;
 #[cfg(windows)]
 pub(crate) use windows::WinApiAlternateScreen;
 
 pub(crate) mod ansi;
+mod builder;
+#[cfg(unix)]
+pub(crate) mod terminfo;
 #[cfg(windows)]
 pub(crate) mod windows;
 
+const ALTERNATE_SCREEN_STATE_CHANGE: &str = "alternate screen";
+// The environment variable which disables the alternate screen
+const NO_ALTERNATE_SCREEN_VAR: &str = "CROSSTERM_NO_ALT_SCREEN";
+// The environment variable which forces (`1`) or forbids (`0`) the ANSI alternate screen
+#[cfg(windows)]
+const FORCE_ANSI_VAR: &str = "CROSSTERM_FORCE_ANSI";
+
+lazy_static! {
+    // How many times the alternate screen was entered (and not left), the main screen
+    // is shown again when the outermost one is left
+    static ref ALTERNATE_SCREEN_DEPTH: Mutex<usize> = Mutex::new(0);
+}
+
+// Locks the nesting depth, a poisoned lock is recovered (the depth is always consistent)
+fn lock_depth() -> MutexGuard<'static, usize> {
+    ALTERNATE_SCREEN_DEPTH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+// `true` if the alternate screen was entered (and not left) via this crate
+static ALTERNATE_SCREEN_ACTIVE: AtomicBool = AtomicBool::new(false);
+// `true` if the alternate screen sequences were written to the standard error
+static ALTERNATE_SCREEN_ON_STDERR: AtomicBool = AtomicBool::new(false);
+// The flavor of the alternate screen which was entered (`Flavor` as `u8`)
+static ALTERNATE_SCREEN_FLAVOR: AtomicU8 = AtomicU8::new(Flavor::Cleared as u8);
+// `true` if the alternate screen was disabled programmatically
+static ALTERNATE_SCREEN_DISABLED: AtomicBool = AtomicBool::new(false);
+// Whether the cleared alternate screen is entered as the composed one, `COMPOSED_AUTO` if
+// it's detected from `$TERM`
+static COMPOSED_CLEARED: AtomicU8 = AtomicU8::new(COMPOSED_AUTO);
+
+// Whether the ANSI alternate screen is forced (`ANSI_FORCED`) or forbidden
+// (`ANSI_FORBIDDEN`), `ANSI_AUTO` if it's detected
+static FORCED_ANSI: AtomicU8 = AtomicU8::new(ANSI_AUTO);
+
+const ANSI_AUTO: u8 = 0;
+const ANSI_FORBIDDEN: u8 = 1;
+const ANSI_FORCED: u8 = 2;
+
+const COMPOSED_AUTO: u8 = 0;
+const COMPOSED_NEVER: u8 = 1;
+const COMPOSED_ALWAYS: u8 = 2;
+
+/// The stream the alternate screen sequences are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Output {
+    Stdout,
+    Stderr,
+}
+
+/// The flavor of the alternate screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    // The cursor is saved and the alternate screen is cleared (`?1049h`)
+    Cleared,
+    // The cursor is not saved and the alternate screen is not cleared (`?47h`)
+    Preserved,
+    // `?1048h` + `?47h` + clear, for terminals which mishandle `?1049h`
+    Composed,
+}
+
+pub(crate) fn is_active() -> bool {
+    ALTERNATE_SCREEN_ACTIVE.load(Ordering::SeqCst)
+}
+
+// Returns the stream the active alternate screen was entered on
+pub(crate) fn active_output() -> Output {
+    if ALTERNATE_SCREEN_ON_STDERR.load(Ordering::SeqCst) {
+        Output::Stderr
+    } else {
+        Output::Stdout
+    }
+}
+
+// Returns the flavor of the active alternate screen
+pub(crate) fn flavor() -> Flavor {
+    match ALTERNATE_SCREEN_FLAVOR.load(Ordering::SeqCst) {
+        x if x == Flavor::Preserved as u8 => Flavor::Preserved,
+        x if x == Flavor::Composed as u8 => Flavor::Composed,
+        _ => Flavor::Cleared,
+    }
+}
+
+pub(crate) fn set_flavor(flavor: Flavor) {
+    ALTERNATE_SCREEN_FLAVOR.store(flavor as u8, Ordering::SeqCst);
+}
+
+// Returns the flavor to enter in place of the given one - GNU screen (and old tmux,
+// `TERM=screen*`) handle the composed sequences better than `?1049h`. The custom
+// sequences are never replaced by the detection.
+pub(crate) fn resolve_flavor(flavor: Flavor) -> Flavor {
+    if flavor != Flavor::Cleared {
+        return flavor;
+    }
+
+    let composed = match COMPOSED_CLEARED.load(Ordering::SeqCst) {
+        COMPOSED_NEVER => false,
+        COMPOSED_ALWAYS => true,
+        _ => {
+            !ansi::has_custom_sequences()
+                && std::env::var("TERM").is_ok_and(|term| term.starts_with("screen"))
+        }
+    };
+
+    if composed {
+        Flavor::Composed
+    } else {
+        Flavor::Cleared
+    }
+}
+
+// Forces (or detects with `None`) the composed sequences in place of the cleared ones
+pub(crate) fn force_composed(composed: Option<bool>) {
+    let value = match composed {
+        None => COMPOSED_AUTO,
+        Some(false) => COMPOSED_NEVER,
+        Some(true) => COMPOSED_ALWAYS,
+    };
+
+    COMPOSED_CLEARED.store(value, Ordering::SeqCst);
+}
+
+// Checks whether the alternate screen is disabled (programmatically or via the
+// `CROSSTERM_NO_ALT_SCREEN` environment variable)
+pub(crate) fn is_disabled() -> bool {
+    if ALTERNATE_SCREEN_DISABLED.load(Ordering::SeqCst) || dumb::suppressed() {
+        return true;
+    }
+
+    match std::env::var_os(NO_ALTERNATE_SCREEN_VAR) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+pub(crate) fn force_disable() {
+    ALTERNATE_SCREEN_DISABLED.store(true, Ordering::SeqCst);
+}
+
+pub(crate) fn set_active(active: bool, output: Output) {
+    let was_active = ALTERNATE_SCREEN_ACTIVE.swap(active, Ordering::SeqCst);
+    ALTERNATE_SCREEN_ON_STDERR.store(output == Output::Stderr, Ordering::SeqCst);
+
+    if was_active != active {
+        #[cfg(feature = "recording")]
+        crate::recording::record(if active {
+            crate::RecordedEvent::AlternateScreenEntered
+        } else {
+            crate::RecordedEvent::AlternateScreenLeft
+        });
+    }
+
+    if active {
+        crate::snapshot::clear_shadow();
+        restore::register(ALTERNATE_SCREEN_STATE_CHANGE, || {
+            *lock_depth() = 0;
+            alternate_screen(active_output(), flavor()).leave()
+        });
+    } else {
+        restore::unregister(ALTERNATE_SCREEN_STATE_CHANGE);
+    }
+}
+
+// Checks whether the terminal supports the alternate screen
+#[cfg(unix)]
+pub(crate) fn is_supported() -> bool {
+    terminfo::is_supported()
+}
+
+// Checks whether the console supports the alternate screen
+//
+// The ANSI alternate screen is used if the console supports the virtual terminal
+// sequences (Windows 10+) or it's a pseudo console. Older consoles switch the console
+// screen buffers, which requires the standard output to be a console.
+#[cfg(windows)]
+pub(crate) fn is_supported() -> bool {
+    use crossterm_winapi::{ConsoleMode, Handle};
+
+    if prefers_ansi() {
+        return true;
+    }
+
+    match Handle::output_handle() {
+        Ok(handle) => ConsoleMode::from(handle).mode().is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Enters the alternate screen if it's the outermost one, increments the nesting depth otherwise
+pub(crate) fn enter_nested(enter: impl FnOnce() -> Result<()>) -> Result<()> {
+    let mut depth = lock_depth();
+
+    if *depth == 0 {
+        enter()?;
+    }
+
+    // Keep it last - increment the depth only if we were able to switch to the alternate screen
+    *depth += 1;
+    Ok(())
+}
+
+// Leaves the alternate screen if it's the outermost one, decrements the nesting depth otherwise
+pub(crate) fn leave_nested(leave: impl FnOnce() -> Result<()>) -> Result<()> {
+    let mut depth = lock_depth();
+
+    if *depth <= 1 {
+        leave()?;
+        *depth = 0;
+    } else {
+        *depth -= 1;
+    }
+
+    Ok(())
+}
+
+// Forgets the nesting depth and the active alternate screen (the terminal was replaced)
+#[cfg(feature = "recording")]
+pub(crate) fn forget_nesting() {
+    *lock_depth() = 0;
+    set_active(false, Output::Stdout);
+}
+
 pub(crate) trait AlternateScreen: Sync + Send {
     fn enter(&self) -> Result<()>;
     fn leave(&self) -> Result<()>;
+    // Writes the sequence to the same output as the alternate screen sequences
+    fn write(&self, sequence: &str) -> Result<()>;
+}
+
+/// Terminal features enabled together with the alternate screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Features {
+    pub(crate) hide_cursor: bool,
+    pub(crate) mouse_capture: bool,
+    pub(crate) bracketed_paste: bool,
+    pub(crate) clear_on_leave: bool,
+}
+
+impl Features {
+    // Returns the sequences enabling the features
+    pub(crate) fn enable_sequence(&self) -> String {
+        let mut sequence = String::new();
+
+        if dumb::suppressed() {
+            return sequence;
+        }
+
+        if self.hide_cursor {
+            sequence.push_str(ansi::hide_cursor_sequence());
+        }
+
+        if self.mouse_capture {
+            sequence.push_str(ansi::ENABLE_MOUSE_DRAG_CSI_SEQUENCE);
+        }
+
+        if self.bracketed_paste {
+            sequence.push_str(ansi::ENABLE_BRACKETED_PASTE_CSI_SEQUENCE);
+        }
+
+        sequence
+    }
+
+    // Returns the sequences disabling the features (in the reverse order)
+    pub(crate) fn disable_sequence(&self) -> String {
+        let mut sequence = String::new();
+
+        if dumb::suppressed() {
+            return sequence;
+        }
+
+        if self.bracketed_paste {
+            sequence.push_str(ansi::DISABLE_BRACKETED_PASTE_CSI_SEQUENCE);
+        }
+
+        if self.mouse_capture {
+            sequence.push_str(ansi::DISABLE_MOUSE_CAPTURE_CSI_SEQUENCE);
+        }
+
+        if self.hide_cursor {
+            sequence.push_str(ansi::show_cursor_sequence());
+        }
+
+        // Never clear the main screen if the alternate screen is disabled
+        if self.clear_on_leave && !is_disabled() {
+            sequence.push_str(ansi::CLEAR_SCREEN_CSI_SEQUENCE);
+        }
+
+        sequence
+    }
+}
+
+// The alternate screen used when it's disabled, it never enters the alternate screen
+// and leaves it only if it was entered before it was disabled
+pub(crate) struct DisabledAlternateScreen {
+    alternate: Box<dyn AlternateScreen + Send + Sync>,
+    // `true` if the alternate screen is tracked by this crate (`is_active`)
+    tracked: bool,
+}
+
+impl DisabledAlternateScreen {
+    pub(crate) fn new(
+        alternate: Box<dyn AlternateScreen + Send + Sync>,
+        tracked: bool,
+    ) -> DisabledAlternateScreen {
+        DisabledAlternateScreen { alternate, tracked }
+    }
+}
+
+impl AlternateScreen for DisabledAlternateScreen {
+    fn enter(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn leave(&self) -> Result<()> {
+        if self.tracked && is_active() {
+            self.alternate.leave()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write(&self, sequence: &str) -> Result<()> {
+        self.alternate.write(sequence)
+    }
+}
+
+pub(crate) fn alternate_screen(
+    output: Output,
+    flavor: Flavor,
+) -> Box<dyn AlternateScreen + Send + Sync> {
+    let alternate = platform_alternate_screen(output, flavor);
+
+    if is_disabled() {
+        Box::new(DisabledAlternateScreen::new(alternate, true))
+    } else {
+        alternate
+    }
+}
+
+// Forces (`true`) or forbids (`false`) the ANSI alternate screen
+pub(crate) fn force_ansi(ansi: bool) {
+    let value = if ansi { ANSI_FORCED } else { ANSI_FORBIDDEN };
+    FORCED_ANSI.store(value, Ordering::SeqCst);
+}
+
+// Checks whether the ANSI alternate screen is used - forced via `force_ansi` or the
+// `CROSSTERM_FORCE_ANSI` environment variable, detected otherwise. The legacy console
+// screen buffers are never used under the pseudo console (ConPTY) and the Cygwin/MSYS
+// pseudo terminal (Mintty).
+#[cfg(windows)]
+pub(crate) fn prefers_ansi() -> bool {
+    match FORCED_ANSI.load(Ordering::SeqCst) {
+        ANSI_FORCED => return true,
+        ANSI_FORBIDDEN => return false,
+        _ => {}
+    }
+
+    match std::env::var(FORCE_ANSI_VAR).as_ref().map(String::as_str) {
+        Ok("1") => true,
+        Ok("0") => false,
+        _ => supports_ansi() || windows::is_pseudo_console() || crate::sys::msys::is_msys_output(),
+    }
 }
 
+// The WinAPI alternate screen ignores the output, the standard output and error share
+// the console screen buffer
 #[cfg(windows)]
-pub(crate) fn alternate_screen() -> Box<dyn AlternateScreen + Send + Sync> {
-    if supports_ansi() {
-        Box::new(AnsiAlternateScreen)
+fn platform_alternate_screen(
+    output: Output,
+    flavor: Flavor,
+) -> Box<dyn AlternateScreen + Send + Sync> {
+    if prefers_ansi() {
+        Box::new(AnsiAlternateScreen::new(output, flavor))
     } else {
         Box::new(WinApiAlternateScreen)
     }
 }
 
 #[cfg(unix)]
-pub(crate) fn alternate_screen() -> AnsiAlternateScreen {
-    AnsiAlternateScreen
+fn platform_alternate_screen(
+    output: Output,
+    flavor: Flavor,
+) -> Box<dyn AlternateScreen + Send + Sync> {
+    Box::new(AnsiAlternateScreen::new(output, flavor))
 }
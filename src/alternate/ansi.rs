@@ -1,20 +1,343 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
 use crossterm_utils::{csi, write_cout, Result};
+use lazy_static::lazy_static;
+
+#[cfg(all(unix, feature = "terminfo"))]
+use super::terminfo;
+use super::{flavor, resolve_flavor, set_active, set_flavor, AlternateScreen, Flavor, Output};
+
+// Disables the mouse capture - all the tracking modes (`?1000`, `?1002`, `?1003`) and
+// the encodings (`?1006`, `?1015`)
+macro_rules! disable_mouse_capture_csi_sequence {
+    () => {
+        csi!(
+            "?1006l",
+            "\x1B[?1015l",
+            "\x1B[?1003l",
+            "\x1B[?1002l",
+            "\x1B[?1000l"
+        )
+    };
+}
+
+// Resets the modes an application can leave behind on the alternate screen - the scroll
+// region (`DECSTBM`), the origin mode (`DECOM`), the line wrap (`DECAWM`), the mouse capture,
+// the bracketed paste, the focus reporting, and the application keypad (`DECKPNM`). Some
+// terminals share them with the main screen.
+macro_rules! reset_modes_csi_sequence {
+    () => {
+        concat!(
+            csi!("r"),
+            csi!("?6l"),
+            csi!("?7h"),
+            disable_mouse_capture_csi_sequence!(),
+            csi!("?2004l"),
+            csi!("?1004l"),
+            "\x1B>"
+        )
+    };
+}
+
+/// Enters the cleared alternate screen and saves the cursor (`?1049h`).
+pub static ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = csi!("?1049h");
+/// Leaves the cleared alternate screen and restores the cursor (`?1049l`).
+pub static LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = csi!("?1049l");
+/// Enters the preserved alternate screen (`?47h`).
+pub static ENTER_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = csi!("?47h");
+/// Leaves the preserved alternate screen (`?47l`).
+pub static LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = csi!("?47l");
+/// Resets the modes and leaves the cleared alternate screen (`?1049l`).
+pub static RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    concat!(reset_modes_csi_sequence!(), csi!("?1049l"));
+/// Resets the modes and leaves the preserved alternate screen (`?47l`).
+pub static RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    concat!(reset_modes_csi_sequence!(), csi!("?47l"));
+pub(crate) static ENTER_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    csi!("?1048h", "\x1B[?47h", "\x1B[2J");
+pub(crate) static LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = csi!("?47l", "\x1B[?1048l");
+pub(crate) static RESET_AND_LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    concat!(reset_modes_csi_sequence!(), csi!("?47l", "\x1B[?1048l"));
+pub(crate) static CLEAR_RESET_AND_LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str = concat!(
+    csi!("2J"),
+    reset_modes_csi_sequence!(),
+    csi!("?47l", "\x1B[?1048l")
+);
+pub(crate) static RESET_SCREEN_STATE_CSI_SEQUENCE: &str =
+    concat!(reset_modes_csi_sequence!(), csi!("?1049l"), csi!("?25h"));
+pub(crate) static SAVE_CURSOR_CSI_SEQUENCE: &str = csi!("?1048h");
+pub(crate) static RESTORE_CURSOR_CSI_SEQUENCE: &str = csi!("?1048l");
+pub(crate) static CLEAR_RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    concat!(csi!("2J"), reset_modes_csi_sequence!(), csi!("?1049l"));
+pub(crate) static CLEAR_RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE: &str =
+    concat!(csi!("2J"), reset_modes_csi_sequence!(), csi!("?47l"));
+pub(crate) static CLEAR_SCREEN_CSI_SEQUENCE: &str = csi!("2J");
+pub(crate) static MOVE_TO_ORIGIN_CSI_SEQUENCE: &str = csi!("1;1H");
+static HIDE_CURSOR_CSI_SEQUENCE: &str = csi!("?25l");
+static SHOW_CURSOR_CSI_SEQUENCE: &str = csi!("?25h");
+pub(crate) static ENABLE_MOUSE_CLICKS_CSI_SEQUENCE: &str = csi!("?1000h", "\x1B[?1006h");
+pub(crate) static ENABLE_MOUSE_DRAG_CSI_SEQUENCE: &str =
+    csi!("?1000h", "\x1B[?1002h", "\x1B[?1006h");
+pub(crate) static ENABLE_MOUSE_ALL_MOTION_CSI_SEQUENCE: &str =
+    csi!("?1000h", "\x1B[?1002h", "\x1B[?1003h", "\x1B[?1006h");
+pub(crate) static DISABLE_MOUSE_CAPTURE_CSI_SEQUENCE: &str = disable_mouse_capture_csi_sequence!();
+pub(crate) static ENABLE_BRACKETED_PASTE_CSI_SEQUENCE: &str = csi!("?2004h");
+pub(crate) static DISABLE_BRACKETED_PASTE_CSI_SEQUENCE: &str = csi!("?2004l");
+
+lazy_static! {
+    // The enter & leave sequences of the cleared alternate screen set by the user
+    static ref CUSTOM_SEQUENCES: Mutex<Option<(&'static str, &'static str)>> = Mutex::new(None);
+}
+
+#[cfg(all(unix, feature = "terminfo"))]
+lazy_static! {
+    // The sequences of the terminfo entry, `None` if there's no entry (the hard-coded
+    // xterm sequences are used then)
+    static ref TERMINFO_SEQUENCES: Option<TerminfoSequences> =
+        terminfo::capabilities().map(TerminfoSequences::new);
+}
+
+// The cleared alternate screen & cursor sequences of the terminfo entry, the capabilities
+// which are not declared are empty (like `screen` without the alternate screen)
+#[cfg(all(unix, feature = "terminfo"))]
+struct TerminfoSequences {
+    enter: String,
+    leave: String,
+    reset_and_leave: String,
+    clear_reset_and_leave: String,
+    hide_cursor: String,
+    show_cursor: String,
+}
 
-use super::AlternateScreen;
+#[cfg(all(unix, feature = "terminfo"))]
+impl TerminfoSequences {
+    fn new(capabilities: terminfo::Capabilities) -> TerminfoSequences {
+        let leave = capabilities.exit_ca_mode.unwrap_or_default();
+
+        TerminfoSequences {
+            enter: capabilities.enter_ca_mode.unwrap_or_default(),
+            reset_and_leave: format!("{}{}", reset_modes_csi_sequence!(), leave),
+            clear_reset_and_leave: format!(
+                "{}{}{}",
+                CLEAR_SCREEN_CSI_SEQUENCE,
+                reset_modes_csi_sequence!(),
+                leave
+            ),
+            leave,
+            hide_cursor: capabilities.cursor_invisible.unwrap_or_default(),
+            show_cursor: capabilities.cursor_normal.unwrap_or_default(),
+        }
+    }
+}
 
-pub(crate) static ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE: &'static str = csi!("?1049h");
-pub(crate) static LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE: &'static str = csi!("?1049l");
+pub(crate) struct AnsiAlternateScreen {
+    output: Output,
+    flavor: Flavor,
+}
 
-pub(crate) struct AnsiAlternateScreen;
+impl AnsiAlternateScreen {
+    pub(crate) fn new(output: Output, flavor: Flavor) -> AnsiAlternateScreen {
+        AnsiAlternateScreen {
+            output,
+            flavor: resolve_flavor(flavor),
+        }
+    }
+}
 
 impl AlternateScreen for AnsiAlternateScreen {
     fn enter(&self) -> Result<()> {
-        write_cout!(ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE)?;
+        // Best effort, the output is not a console under Mintty (see `force_ansi`)
+        #[cfg(windows)]
+        let _ = super::windows::enable_virtual_terminal_processing();
+
+        write_sequence(self.output, &enter_sequence(self.flavor))?;
+        set_flavor(self.flavor);
+        set_active(true, self.output);
         Ok(())
     }
 
     fn leave(&self) -> Result<()> {
-        write_cout!(LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE)?;
+        write_sequence(self.output, &reset_and_leave_sequence())?;
+        set_active(false, self.output);
+
+        #[cfg(windows)]
+        super::windows::restore_output_mode()?;
+        Ok(())
+    }
+
+    fn write(&self, sequence: &str) -> Result<()> {
+        write_sequence(self.output, sequence)
+    }
+}
+
+// The alternate screen of an arbitrary writer (like a PTY), it's not tracked by this crate
+pub(crate) struct WriterAlternateScreen {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl WriterAlternateScreen {
+    pub(crate) fn new(writer: Box<dyn Write + Send>) -> WriterAlternateScreen {
+        WriterAlternateScreen {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl AlternateScreen for WriterAlternateScreen {
+    fn enter(&self) -> Result<()> {
+        self.write(ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE)
+    }
+
+    fn leave(&self) -> Result<()> {
+        self.write(RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE)
+    }
+
+    fn write(&self, sequence: &str) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.write_all(sequence.as_bytes())?;
+        writer.flush()?;
         Ok(())
     }
 }
+
+// Returns the sequence to enter the alternate screen of the given flavor
+pub(crate) fn enter_sequence(flavor: Flavor) -> Cow<'static, str> {
+    if let (Flavor::Cleared, Some((enter, _))) = (flavor, custom_sequences()) {
+        return Cow::Borrowed(enter);
+    }
+
+    #[cfg(all(unix, feature = "terminfo"))]
+    {
+        if let (Flavor::Cleared, Some(sequences)) = (flavor, TERMINFO_SEQUENCES.as_ref()) {
+            return Cow::Borrowed(&sequences.enter);
+        }
+    }
+
+    Cow::Borrowed(match flavor {
+        Flavor::Cleared => ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        Flavor::Preserved => ENTER_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        Flavor::Composed => ENTER_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    })
+}
+
+// Returns the sequence to leave the alternate screen which was entered
+pub(crate) fn leave_sequence() -> Cow<'static, str> {
+    if let (Flavor::Cleared, Some((_, leave))) = (flavor(), custom_sequences()) {
+        return Cow::Borrowed(leave);
+    }
+
+    #[cfg(all(unix, feature = "terminfo"))]
+    {
+        if let (Flavor::Cleared, Some(sequences)) = (flavor(), TERMINFO_SEQUENCES.as_ref()) {
+            return Cow::Borrowed(&sequences.leave);
+        }
+    }
+
+    Cow::Borrowed(match flavor() {
+        Flavor::Cleared => LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        Flavor::Preserved => LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        Flavor::Composed => LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    })
+}
+
+// Returns the sequence to reset the modes and to leave the alternate screen which was entered
+pub(crate) fn reset_and_leave_sequence() -> Cow<'static, str> {
+    clear_reset_and_leave_sequence(false)
+}
+
+// Returns the sequence to clear the alternate screen (optionally), reset the modes, and to
+// leave the alternate screen which was entered
+pub(crate) fn clear_reset_and_leave_sequence(clear: bool) -> Cow<'static, str> {
+    clear_reset_and_leave_sequence_for(flavor(), clear)
+}
+
+// Returns the sequence to clear the alternate screen (optionally), reset the modes, and to
+// leave the alternate screen of the given flavor
+pub(crate) fn clear_reset_and_leave_sequence_for(flavor: Flavor, clear: bool) -> Cow<'static, str> {
+    if let (Flavor::Cleared, Some((_, leave))) = (flavor, custom_sequences()) {
+        let clear = if clear { CLEAR_SCREEN_CSI_SEQUENCE } else { "" };
+        return Cow::Owned(format!("{}{}{}", clear, reset_modes_csi_sequence!(), leave));
+    }
+
+    #[cfg(all(unix, feature = "terminfo"))]
+    {
+        if let (Flavor::Cleared, Some(sequences)) = (flavor, TERMINFO_SEQUENCES.as_ref()) {
+            return Cow::Borrowed(if clear {
+                &sequences.clear_reset_and_leave
+            } else {
+                &sequences.reset_and_leave
+            });
+        }
+    }
+
+    Cow::Borrowed(match (clear, flavor) {
+        (false, Flavor::Cleared) => RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        (false, Flavor::Preserved) => RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        (false, Flavor::Composed) => RESET_AND_LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        (true, Flavor::Cleared) => CLEAR_RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        (true, Flavor::Preserved) => CLEAR_RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+        (true, Flavor::Composed) => CLEAR_RESET_AND_LEAVE_COMPOSED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    })
+}
+
+// Sets (or resets with `None`) the enter & leave sequences of the cleared alternate screen
+pub(crate) fn set_custom_sequences(sequences: Option<(&'static str, &'static str)>) {
+    *CUSTOM_SEQUENCES.lock().unwrap_or_else(|e| e.into_inner()) = sequences;
+}
+
+pub(crate) fn has_custom_sequences() -> bool {
+    custom_sequences().is_some()
+}
+
+fn custom_sequences() -> Option<(&'static str, &'static str)> {
+    *CUSTOM_SEQUENCES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Returns the sequence to hide the cursor
+pub(crate) fn hide_cursor_sequence() -> &'static str {
+    #[cfg(all(unix, feature = "terminfo"))]
+    {
+        if let Some(sequences) = TERMINFO_SEQUENCES.as_ref() {
+            return &sequences.hide_cursor;
+        }
+    }
+
+    HIDE_CURSOR_CSI_SEQUENCE
+}
+
+// Returns the sequence to show the cursor
+pub(crate) fn show_cursor_sequence() -> &'static str {
+    #[cfg(all(unix, feature = "terminfo"))]
+    {
+        if let Some(sequences) = TERMINFO_SEQUENCES.as_ref() {
+            return &sequences.show_cursor;
+        }
+    }
+
+    SHOW_CURSOR_CSI_SEQUENCE
+}
+
+// Writes & flushes the sequence to the given output, it's recorded instead if there's
+// a `RecordingScreen`
+pub(crate) fn write_sequence(output: Output, sequence: &str) -> Result<()> {
+    #[cfg(feature = "recording")]
+    {
+        if crate::recording::capture(sequence) {
+            return Ok(());
+        }
+    }
+
+    match output {
+        Output::Stdout => {
+            write_cout!(sequence)?;
+        }
+        Output::Stderr => {
+            let mut stderr = io::stderr();
+            stderr.write_all(sequence.as_bytes())?;
+            stderr.flush()?;
+        }
+    }
+    Ok(())
+}
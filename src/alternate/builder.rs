@@ -0,0 +1,111 @@
+use crossterm_utils::Result;
+
+use super::{alternate_screen, enter_nested, Features, Flavor, Output};
+use crate::{dumb, AlternateScreen};
+
+/// A builder to switch to the alternate screen with bundled options.
+///
+/// Everything is applied when the alternate screen is entered (the alternate screen,
+/// the features, and the raw mode) and reverted in the reverse order when the
+/// [`AlternateScreen`](struct.AlternateScreen.html) value is dropped.
+///
+/// The cursor, the mouse capture, and the bracketed paste options are ignored by the
+/// legacy Windows console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::AlternateScreen;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let _alternate = AlternateScreen::builder()
+///         .raw_mode(true)
+///         .hide_cursor(true)
+///         .mouse_capture(true)
+///         .bracketed_paste(true)
+///         .enter()?;
+///
+///     // Do something on the alternate screen
+///
+///     Ok(())
+/// } // `_alternate` dropped here <- everything reverted & back to main screen
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AlternateScreenBuilder {
+    raw_mode: bool,
+    clear: bool,
+    features: Features,
+}
+
+impl AlternateScreenBuilder {
+    pub(crate) fn new() -> AlternateScreenBuilder {
+        AlternateScreenBuilder {
+            raw_mode: false,
+            clear: true,
+            features: Features::default(),
+        }
+    }
+
+    /// Enables the raw mode (disabled by default).
+    pub fn raw_mode(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.raw_mode = enabled;
+        self
+    }
+
+    /// Clears the alternate screen & saves the cursor (`?1049h`) or not (`?47h`).
+    ///
+    /// Enabled by default. See the
+    /// [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) documentation for more
+    /// information.
+    pub fn clear(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.clear = enabled;
+        self
+    }
+
+    /// Hides the cursor (disabled by default).
+    pub fn hide_cursor(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.features.hide_cursor = enabled;
+        self
+    }
+
+    /// Enables the mouse capture (disabled by default).
+    pub fn mouse_capture(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.features.mouse_capture = enabled;
+        self
+    }
+
+    /// Enables the bracketed paste (disabled by default).
+    pub fn bracketed_paste(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.features.bracketed_paste = enabled;
+        self
+    }
+
+    /// Clears the alternate screen before the main screen is shown (disabled by default).
+    ///
+    /// Some terminals briefly flash the stale content when the alternate screen is
+    /// entered again later.
+    pub fn clear_on_leave(mut self, enabled: bool) -> AlternateScreenBuilder {
+        self.features.clear_on_leave = enabled;
+        self
+    }
+
+    /// Switches to the alternate screen.
+    ///
+    /// You'll be automatically switched to the main screen (and everything is reverted)
+    /// if this function fails.
+    pub fn enter(self) -> Result<AlternateScreen> {
+        dumb::check()?;
+
+        let flavor = if self.clear {
+            Flavor::Cleared
+        } else {
+            Flavor::Preserved
+        };
+
+        let alternate = alternate_screen(Output::Stdout, flavor);
+        enter_nested(|| alternate.enter())?;
+
+        AlternateScreen::new(alternate, true, self.features, self.raw_mode)
+    }
+}
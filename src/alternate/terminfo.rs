@@ -0,0 +1,220 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// The magic number of the legacy (16-bit numbers) terminfo format
+const MAGIC_LEGACY: u16 = 0o432;
+// The magic number of the extended (32-bit numbers) terminfo format
+const MAGIC_EXTENDED: u16 = 0o1036;
+
+// Indexes of the `civis` (`cursor_invisible`) & `cnorm` (`cursor_normal`) string capabilities
+#[cfg(feature = "terminfo")]
+const CURSOR_INVISIBLE: usize = 13;
+#[cfg(feature = "terminfo")]
+const CURSOR_NORMAL: usize = 16;
+// Index of the `csr` (`change_scroll_region`) string capability
+const CHANGE_SCROLL_REGION: usize = 3;
+// Indexes of the `smcup` (`enter_ca_mode`) & `rmcup` (`exit_ca_mode`) string capabilities
+const ENTER_CA_MODE: usize = 28;
+const EXIT_CA_MODE: usize = 40;
+
+// The string capabilities of the terminfo entry used by this crate, `None` if the
+// capability is not declared
+#[cfg(feature = "terminfo")]
+pub(crate) struct Capabilities {
+    pub(crate) enter_ca_mode: Option<String>,
+    pub(crate) exit_ca_mode: Option<String>,
+    pub(crate) cursor_invisible: Option<String>,
+    pub(crate) cursor_normal: Option<String>,
+}
+
+// Checks whether the terminal supports the alternate screen
+//
+// `$TERM` must be set (and not `dumb`). The compiled terminfo entry is consulted if it
+// exists, the terminal must declare both the `smcup` & `rmcup` capabilities. Terminals
+// without the terminfo entry are assumed to support it.
+pub(crate) fn is_supported() -> bool {
+    let term = match env::var("TERM") {
+        Ok(term) => term,
+        Err(_) => return false,
+    };
+
+    if term.is_empty() || term == "dumb" {
+        return false;
+    }
+
+    match read_entry(&term) {
+        Some(entry) => has_alternate_screen(&entry).unwrap_or(true),
+        None => true,
+    }
+}
+
+// Checks whether the terminal declares the scroll region (`csr`) capability, `None` if
+// `$TERM` is not set or the terminfo entry doesn't exist (or it's malformed)
+pub(crate) fn has_scroll_region() -> Option<bool> {
+    let term = env::var("TERM").ok()?;
+    let entry = read_entry(&term)?;
+    let strings = StringCapabilities::parse(&entry)?;
+
+    Some(strings.offset(CHANGE_SCROLL_REGION)?.is_some())
+}
+
+// Reads the string capabilities of the terminal, `None` if `$TERM` is not set or the
+// terminfo entry doesn't exist (or it's malformed)
+#[cfg(feature = "terminfo")]
+pub(crate) fn capabilities() -> Option<Capabilities> {
+    let term = env::var("TERM").ok()?;
+    let entry = read_entry(&term)?;
+    let strings = StringCapabilities::parse(&entry)?;
+
+    Some(Capabilities {
+        enter_ca_mode: strings.get(ENTER_CA_MODE),
+        exit_ca_mode: strings.get(EXIT_CA_MODE),
+        cursor_invisible: strings.get(CURSOR_INVISIBLE),
+        cursor_normal: strings.get(CURSOR_NORMAL),
+    })
+}
+
+// Reads the compiled terminfo entry of the terminal
+fn read_entry(term: &str) -> Option<Vec<u8>> {
+    let first = term.chars().next()?;
+
+    search_directories().into_iter().find_map(|directory| {
+        // Both the `x/xterm` & `78/xterm` (macOS) layouts are in use
+        fs::read(directory.join(first.to_string()).join(term))
+            .or_else(|_| fs::read(directory.join(format!("{:x}", first as u32)).join(term)))
+            .ok()
+    })
+}
+
+// Returns the terminfo directories in the `ncurses` search order
+fn search_directories() -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+
+    if let Some(directory) = env::var_os("TERMINFO") {
+        directories.push(PathBuf::from(directory));
+    }
+
+    if let Some(home) = env::var_os("HOME") {
+        directories.push(PathBuf::from(home).join(".terminfo"));
+    }
+
+    if let Ok(dirs) = env::var("TERMINFO_DIRS") {
+        directories.extend(
+            dirs.split(':')
+                .filter(|directory| !directory.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+
+    directories.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    directories
+}
+
+// Checks whether the entry declares both the `smcup` & `rmcup` capabilities, `None` if
+// the entry is malformed
+fn has_alternate_screen(entry: &[u8]) -> Option<bool> {
+    let strings = StringCapabilities::parse(entry)?;
+
+    Some(strings.offset(ENTER_CA_MODE)?.is_some() && strings.offset(EXIT_CA_MODE)?.is_some())
+}
+
+// The string capabilities section of a compiled terminfo entry
+struct StringCapabilities<'a> {
+    entry: &'a [u8],
+    // Where the offsets of the strings start
+    offsets: usize,
+    count: usize,
+}
+
+impl<'a> StringCapabilities<'a> {
+    // Parses the header of the entry, `None` if the entry is malformed
+    fn parse(entry: &'a [u8]) -> Option<StringCapabilities<'a>> {
+        let short = |index: usize| -> Option<u16> {
+            let bytes = entry.get(index * 2..index * 2 + 2)?;
+            Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+        };
+
+        let number_size = match short(0)? {
+            MAGIC_LEGACY => 2,
+            MAGIC_EXTENDED => 4,
+            _ => return None,
+        };
+
+        let names_size = short(1)? as usize;
+        let booleans_count = short(2)? as usize;
+        let numbers_count = short(3)? as usize;
+        let count = short(4)? as usize;
+
+        let mut offsets = 12 + names_size + booleans_count;
+        // The numbers section is aligned to an even byte
+        offsets += offsets % 2;
+        offsets += numbers_count * number_size;
+
+        Some(StringCapabilities {
+            entry,
+            offsets,
+            count,
+        })
+    }
+
+    // Returns the offset of the string in the string table, `Some(None)` if the capability
+    // is not declared, `None` if the entry is malformed
+    fn offset(&self, index: usize) -> Option<Option<usize>> {
+        if index >= self.count {
+            return Some(None);
+        }
+
+        let position = self.offsets + index * 2;
+        let bytes = self.entry.get(position..position + 2)?;
+        let offset = i16::from_le_bytes([bytes[0], bytes[1]]);
+
+        // Negative offsets (`-1` absent, `-2` cancelled) mean the capability is not declared
+        if offset < 0 {
+            Some(None)
+        } else {
+            Some(Some(offset as usize))
+        }
+    }
+
+    // Returns the string capability without the padding (`$<5>`), `None` if it's not
+    // declared (or the entry is malformed)
+    #[cfg(feature = "terminfo")]
+    fn get(&self, index: usize) -> Option<String> {
+        // The string table follows the offsets
+        let start = self.offsets + self.count * 2 + self.offset(index)??;
+        let length = self
+            .entry
+            .get(start..)?
+            .iter()
+            .position(|byte| *byte == 0)?;
+        let value = String::from_utf8_lossy(&self.entry[start..start + length]);
+
+        Some(strip_padding(&value))
+    }
+}
+
+// Removes the padding specifications (`$<...>`) from the string capability
+#[cfg(feature = "terminfo")]
+fn strip_padding(value: &str) -> String {
+    let mut stripped = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("$<") {
+        match rest[start..].find('>') {
+            Some(end) => {
+                stripped.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+
+    stripped.push_str(rest);
+    stripped
+}
@@ -1,20 +1,251 @@
+use std::os::windows::io::RawHandle;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::{env, mem};
+
 use crossterm_utils::Result;
-use crossterm_winapi::{Handle, ScreenBuffer};
+use lazy_static::lazy_static;
+use winapi::um::consoleapi::WriteConsoleW;
+use winapi::um::wincon::{
+    GetConsoleScreenBufferInfo, ReadConsoleOutputW, SetConsoleCursorPosition, CHAR_INFO,
+    CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+};
+use winapi::um::winnt::HANDLE;
+
+use super::{set_active, AlternateScreen, Output};
+use crate::sys::winapi::{console, result};
+
+lazy_static! {
+    // The main screen buffer state (cursor, attributes, window) saved when the alternate
+    // screen is entered
+    static ref MAIN_SCREEN_BUFFER_INFO: Mutex<Option<CONSOLE_SCREEN_BUFFER_INFO>> = Mutex::new(None);
+
+    // The active alternate screen buffer, the handle address is stored to keep it `Send`
+    static ref ALTERNATE_SCREEN_BUFFER: Mutex<Option<usize>> = Mutex::new(None);
 
-use super::AlternateScreen;
+    // The output mode prior the virtual terminal processing was enabled by the ANSI
+    // alternate screen
+    static ref ORIGINAL_OUTPUT_MODE: Mutex<Option<u32>> = Mutex::new(None);
+}
+
+// The main screen buffer (the standard output) handle address, 0 if it wasn't looked up yet
+static MAIN_SCREEN_BUFFER: AtomicUsize = AtomicUsize::new(0);
+
+// Returns the main screen buffer handle, it's looked up once
+pub(crate) fn main_screen_buffer() -> Result<HANDLE> {
+    match MAIN_SCREEN_BUFFER.load(Ordering::SeqCst) {
+        0 => {
+            let handle = console().output_handle()? as HANDLE;
+            // Keep it last - remember the handle only if there's one
+            MAIN_SCREEN_BUFFER.store(handle as usize, Ordering::SeqCst);
+            Ok(handle)
+        }
+        handle => Ok(handle as HANDLE),
+    }
+}
+
+// Forgets the main screen buffer handle, it's looked up again (the console was replaced)
+#[cfg(feature = "recording")]
+pub(crate) fn forget_main_screen_buffer() {
+    MAIN_SCREEN_BUFFER.store(0, Ordering::SeqCst);
+}
+
+// Checks whether the console is a pseudo console (ConPTY, like the Windows Terminal)
+//
+// The hosting terminal interprets the virtual terminal sequences there and the console
+// screen buffers misbehave (the output of the new buffer is not shown). The Windows
+// Terminal sets `WT_SESSION`, the other hosts enable the virtual terminal processing.
+pub(crate) fn is_pseudo_console() -> bool {
+    if env::var_os("WT_SESSION").is_some_and(|session| !session.is_empty()) {
+        return true;
+    }
+
+    match main_screen_buffer() {
+        Ok(handle) => console()
+            .mode(handle as RawHandle)
+            .map(|mode| mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0)
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+// Enables the virtual terminal processing of the output, the original output mode is
+// saved (once) and restored by `restore_output_mode`
+pub(crate) fn enable_virtual_terminal_processing() -> Result<()> {
+    let console = console();
+    let handle = main_screen_buffer()? as RawHandle;
+    let mode = console.mode(handle)?;
+
+    if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING == 0 {
+        console.set_mode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING)?;
+    }
+
+    // Keep it last - save the original mode only if we were able to enable it
+    ORIGINAL_OUTPUT_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert(mode);
+
+    Ok(())
+}
+
+// Restores the output mode saved by `enable_virtual_terminal_processing`
+pub(crate) fn restore_output_mode() -> Result<()> {
+    let mut original_mode = ORIGINAL_OUTPUT_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if let Some(mode) = *original_mode {
+        console().set_mode(main_screen_buffer()? as RawHandle, mode)?;
+        // Keep it last - forget the original mode only if we were able to restore it
+        original_mode.take();
+    }
+
+    Ok(())
+}
+
+// Returns the active alternate screen buffer created by the `WinApiAlternateScreen`
+pub(crate) fn alternate_screen_buffer() -> Option<HANDLE> {
+    ALTERNATE_SCREEN_BUFFER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .map(|handle| handle as HANDLE)
+}
 
 pub(crate) struct WinApiAlternateScreen;
 
 impl AlternateScreen for WinApiAlternateScreen {
     fn enter(&self) -> Result<()> {
-        let alternate_screen = ScreenBuffer::create();
-        alternate_screen.show()?;
+        let console = console();
+        let info = main_screen_buffer_info()?;
+
+        let alternate_screen = console.create_screen_buffer()?;
+        fit_to_window(alternate_screen as HANDLE, &info)?;
+        console.show_screen_buffer(alternate_screen)?;
+
+        // Keep it last - save the state only if we were able to switch to the alternate screen
+        *MAIN_SCREEN_BUFFER_INFO
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(info);
+        *ALTERNATE_SCREEN_BUFFER
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(alternate_screen as usize);
+
+        set_active(true, Output::Stdout);
         Ok(())
     }
 
     fn leave(&self) -> Result<()> {
-        let screen_buffer = ScreenBuffer::from(Handle::output_handle()?);
-        screen_buffer.show()?;
+        let console = console();
+        let main_screen = main_screen_buffer()? as RawHandle;
+        console.show_screen_buffer(main_screen)?;
+        set_active(false, Output::Stdout);
+
+        ALTERNATE_SCREEN_BUFFER
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        let info = MAIN_SCREEN_BUFFER_INFO
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        // Restore the main screen buffer state, the alternate screen buffer could have
+        // changed it (like a process writing to the standard output)
+        if let Some(info) = info {
+            console.restore_screen_buffer(main_screen, &info)?;
+        }
+
         Ok(())
     }
+
+    // The legacy console doesn't support the sequences
+    fn write(&self, _sequence: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Resizes the screen buffer to the visible window dimensions of the given screen buffer,
+// the new buffer has no scrollback then (like the ANSI alternate screen)
+pub(crate) fn fit_to_window(buffer: HANDLE, info: &CONSOLE_SCREEN_BUFFER_INFO) -> Result<()> {
+    let size = COORD {
+        X: info.srWindow.Right - info.srWindow.Left + 1,
+        Y: info.srWindow.Bottom - info.srWindow.Top + 1,
+    };
+
+    console().set_screen_buffer_size(buffer as RawHandle, size)
+}
+
+// Returns the screen buffer info of the main screen buffer
+pub(crate) fn main_screen_buffer_info() -> Result<CONSOLE_SCREEN_BUFFER_INFO> {
+    console().screen_buffer_info(main_screen_buffer()? as RawHandle)
+}
+
+// Reads the text of the visible window of the main screen buffer, the trailing spaces
+// of every line are trimmed
+pub(crate) fn read_main_screen_lines() -> Result<Vec<String>> {
+    read_screen_lines(main_screen_buffer()?)
+}
+
+// Reads the text of the visible window of the screen buffer, the trailing spaces of every
+// line are trimmed
+pub(crate) fn read_screen_lines(handle: HANDLE) -> Result<Vec<String>> {
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+    result(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+    let mut region = info.srWindow;
+
+    let size = COORD {
+        X: region.Right - region.Left + 1,
+        Y: region.Bottom - region.Top + 1,
+    };
+
+    let mut cells: Vec<CHAR_INFO> =
+        vec![unsafe { mem::zeroed() }; size.X as usize * size.Y as usize];
+
+    result(unsafe {
+        ReadConsoleOutputW(
+            handle,
+            cells.as_mut_ptr(),
+            size,
+            COORD { X: 0, Y: 0 },
+            &mut region,
+        )
+    })?;
+
+    let lines = cells
+        .chunks(size.X as usize)
+        .map(|row| {
+            let text: Vec<u16> = row
+                .iter()
+                .map(|cell| unsafe { *cell.Char.UnicodeChar() })
+                .collect();
+            String::from_utf16_lossy(&text).trim_end().to_string()
+        })
+        .collect();
+
+    Ok(lines)
+}
+
+// Writes the text to the active alternate screen buffer, from the origin
+pub(crate) fn write_to_alternate_screen_buffer(text: &str) -> Result<()> {
+    let handle = match alternate_screen_buffer() {
+        Some(handle) => handle,
+        None => return Ok(()),
+    };
+
+    result(unsafe { SetConsoleCursorPosition(handle, COORD { X: 0, Y: 0 }) })?;
+
+    let text: Vec<u16> = text.encode_utf16().collect();
+    let mut written = 0;
+
+    result(unsafe {
+        WriteConsoleW(
+            handle,
+            text.as_ptr() as *const _,
+            text.len() as u32,
+            &mut written,
+            std::ptr::null_mut(),
+        )
+    })
 }
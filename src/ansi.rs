@@ -0,0 +1,55 @@
+//! The ANSI escape sequences this crate writes to switch the screens.
+//!
+//! It allows to stream the exact same sequences into your own transport (like an SSH
+//! server or a terminal multiplexer).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::io::Write;
+//! use std::net::TcpStream;
+//! use crossterm_screen::ansi;
+//! use crossterm_utils::Result;
+//!
+//! fn main() -> Result<()> {
+//!     let mut stream = TcpStream::connect("127.0.0.1:2323")?;
+//!
+//!     stream.write_all(ansi::enter_alternate_screen_bytes(false))?;
+//!     // Render the UI to the `stream`
+//!     stream.write_all(ansi::leave_alternate_screen_bytes(false))?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+pub use crate::alternate::ansi::{
+    ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE, ENTER_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE, LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE,
+    RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE,
+};
+
+/// Returns the sequence to enter the cleared (`?1049h`) or the preserved (`?47h`)
+/// alternate screen.
+pub fn enter_alternate_screen_bytes(preserved: bool) -> &'static [u8] {
+    if preserved {
+        ENTER_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE.as_bytes()
+    } else {
+        ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE.as_bytes()
+    }
+}
+
+/// Returns the sequence to leave the cleared (`?1049l`) or the preserved (`?47l`)
+/// alternate screen.
+///
+/// The modes an application can leave behind (the scroll region, the origin mode, the
+/// line wrap, the mouse capture, the bracketed paste, the focus reporting, and the
+/// application keypad) are reset first, the same way the
+/// [`LeaveAlternateScreen`](../struct.LeaveAlternateScreen.html) command does.
+pub fn leave_alternate_screen_bytes(preserved: bool) -> &'static [u8] {
+    if preserved {
+        RESET_AND_LEAVE_PRESERVED_ALTERNATE_SCREEN_CSI_SEQUENCE.as_bytes()
+    } else {
+        RESET_AND_LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE.as_bytes()
+    }
+}
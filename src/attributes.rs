@@ -0,0 +1,129 @@
+use std::fmt::{self, Display, Formatter};
+
+use crossterm_utils::Result;
+
+use crate::sys;
+
+/// A snapshot of the terminal attributes.
+///
+/// It wraps the `termios` structure on UNIX and the console input mode on Windows.
+/// Use it to save, tweak, and restore the terminal state beyond what the
+/// [`RawModeOptions`](struct.RawModeOptions.html) allow.
+///
+/// Attributes applied with the [`apply`](struct.TerminalAttributes.html#method.apply)
+/// method are not tracked by the [`RawScreen`](struct.RawScreen.html). The mode prior
+/// the raw mode is restored when the raw mode is disabled.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::TerminalAttributes;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let original = TerminalAttributes::get()?;
+///
+///     let mut attributes = original;
+///     #[cfg(unix)]
+///     {
+///         attributes.termios_mut().c_lflag &= !libc::ECHO;
+///     }
+///     attributes.apply()?;
+///
+///     for change in original.diff(&attributes) {
+///         println!("{}", change);
+///     }
+///
+///     original.apply()
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct TerminalAttributes {
+    attributes: sys::Attributes,
+}
+
+impl TerminalAttributes {
+    pub(crate) fn new(attributes: sys::Attributes) -> TerminalAttributes {
+        TerminalAttributes { attributes }
+    }
+
+    pub(crate) fn into_inner(self) -> sys::Attributes {
+        self.attributes
+    }
+
+    /// Returns the current attributes of the terminal the process is attached to.
+    pub fn get() -> Result<TerminalAttributes> {
+        Ok(TerminalAttributes::new(
+            sys::RawModeCommand::new()?.attributes()?,
+        ))
+    }
+
+    /// Applies the attributes to the terminal the process is attached to.
+    pub fn apply(&self) -> Result<()> {
+        sys::RawModeCommand::new()?.set_attributes(&self.attributes)
+    }
+
+    /// Returns the `termios` structure.
+    #[cfg(unix)]
+    pub fn termios(&self) -> &libc::termios {
+        &self.attributes
+    }
+
+    /// Returns the mutable `termios` structure.
+    #[cfg(unix)]
+    pub fn termios_mut(&mut self) -> &mut libc::termios {
+        &mut self.attributes
+    }
+
+    /// Returns the console input mode.
+    #[cfg(windows)]
+    pub fn console_mode(&self) -> u32 {
+        self.attributes
+    }
+
+    /// Sets the console input mode.
+    #[cfg(windows)]
+    pub fn set_console_mode(&mut self, mode: u32) {
+        self.attributes = mode;
+    }
+
+    /// Returns the named flags (like `ECHO` or `ENABLE_ECHO_INPUT`) and whether they're set.
+    pub fn flags(&self) -> Vec<(&'static str, bool)> {
+        sys::attribute_flags(&self.attributes)
+    }
+
+    /// Returns the flags which differ between `self` and the `other` attributes.
+    ///
+    /// The `enabled` field of every change says whether the flag is set in the `other`
+    /// attributes.
+    pub fn diff(&self, other: &TerminalAttributes) -> Vec<AttributeChange> {
+        self.flags()
+            .into_iter()
+            .zip(other.flags())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((flag, _), (_, enabled))| AttributeChange { flag, enabled })
+            .collect()
+    }
+}
+
+/// A change of a terminal attribute flag.
+///
+/// See the [`TerminalAttributes::diff`](struct.TerminalAttributes.html#method.diff)
+/// method for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttributeChange {
+    /// The flag name (like `ECHO` or `ENABLE_ECHO_INPUT`).
+    pub flag: &'static str,
+    /// `true` if the flag was enabled, `false` if it was disabled.
+    pub enabled: bool,
+}
+
+impl Display for AttributeChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.enabled {
+            write!(f, "enabled {}", self.flag)
+        } else {
+            write!(f, "disabled {}", self.flag)
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Virtual screen with the diff-based flushing.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use crossterm_utils::{csi, Result};
+
+use crate::snapshot;
+
+// The cell covered by the wide character on its left
+pub(crate) const CONTINUATION: char = '\0';
+
+// The East Asian Wide & Fullwidth ranges (the CJK scripts, the fullwidth forms, and emoji)
+const WIDE_RANGES: &[(u32, u32)] = &[
+    (0x1100, 0x115F),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE30, 0xFE4F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x1F300, 0x1F64F),
+    (0x1F900, 0x1F9FF),
+    (0x20000, 0x3FFFD),
+];
+
+/// A grid of cells the frames are drawn into.
+///
+/// The app draws the whole frame into the virtual screen and the
+/// [`flush`](#method.flush) method writes only the cells which differ from the previous
+/// frame. It means less flicker and less bandwidth (like over SSH) than redrawing the
+/// whole alternate screen. The first frame (and the frame after the
+/// [`resize`](#method.resize) or [`invalidate`](#method.invalidate) methods) is written
+/// as a whole.
+///
+/// Every cell holds a single character. The wide characters (like CJK or emoji, taking
+/// two columns) take two cells, the second one is covered by the character. The
+/// zero-width and the control characters are not supported. The cells outside of the
+/// grid are ignored.
+///
+/// # Notes
+///
+/// The cursor is moved with the ANSI sequences (`CUP`), the legacy Windows console (no
+/// ANSI support) is supported by the [`Presenter`](struct.Presenter.html) only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use crossterm_screen::{size, AlternateScreen, VirtualScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let _alternate = AlternateScreen::to_alternate(true)?;
+///
+///     let (columns, rows) = size()?;
+///     let mut screen = VirtualScreen::new(columns, rows);
+///
+///     for frame in 0..100 {
+///         screen.clear();
+///         screen.print(frame % columns, 0, "Hello");
+///         // Writes the changed cells only
+///         screen.flush(&mut stdout())?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualScreen {
+    columns: u16,
+    rows: u16,
+    // The frame being drawn (row by row)
+    cells: Vec<char>,
+    // The frame written by the last flush, `None` if the whole frame must be written
+    flushed: Option<Arc<Vec<char>>>,
+    // Whether the flushed frames are the contents of the alternate screen
+    captured: bool,
+}
+
+impl VirtualScreen {
+    /// Creates a virtual screen of the given size filled with spaces.
+    pub fn new(columns: u16, rows: u16) -> VirtualScreen {
+        VirtualScreen {
+            columns,
+            rows,
+            cells: vec![' '; columns as usize * rows as usize],
+            flushed: None,
+            captured: false,
+        }
+    }
+
+    /// Returns the size `(columns, rows)`.
+    pub fn size(&self) -> (u16, u16) {
+        (self.columns, self.rows)
+    }
+
+    /// Resizes the virtual screen, it's cleared and the next frame is written as a whole.
+    ///
+    /// Call it when the terminal is resized, see the
+    /// [`resize_events`](fn.resize_events.html) function.
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        *self = VirtualScreen {
+            captured: self.captured,
+            ..VirtualScreen::new(columns, rows)
+        };
+    }
+
+    /// Sets whether the flushed frames are returned by the
+    /// [`AlternateScreen::capture`](struct.AlternateScreen.html#method.capture) method
+    /// (disabled by default).
+    ///
+    /// Enable it for the virtual screen flushed to the terminal, there's a single
+    /// captured frame per process.
+    pub fn capture_frames(&mut self, capture: bool) {
+        self.captured = capture;
+    }
+
+    /// Forces the next frame to be written as a whole.
+    ///
+    /// Use it when something else wrote to the terminal in the meantime.
+    pub fn invalidate(&mut self) {
+        self.flushed = None;
+    }
+
+    /// Fills the whole virtual screen with spaces.
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = ' ';
+        }
+    }
+
+    /// Returns the character of the cell, `None` if it's outside of the grid or it's
+    /// covered by the wide character on its left.
+    pub fn get(&self, column: u16, row: u16) -> Option<char> {
+        self.index(column, row)
+            .map(|index| self.cells[index])
+            .filter(|character| *character != CONTINUATION)
+    }
+
+    /// Sets the character of the cell.
+    ///
+    /// A wide character covers the next cell as well, it's replaced with a space if it
+    /// doesn't fit in the row. The wide characters partially overwritten are replaced with
+    /// spaces.
+    pub fn set(&mut self, column: u16, row: u16, character: char) {
+        let index = match self.index(column, row) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let wide = is_wide(character) && column + 1 < self.columns;
+
+        self.release(index);
+        if wide {
+            self.release(index + 1);
+            self.cells[index + 1] = CONTINUATION;
+        }
+
+        self.cells[index] = if wide || !is_wide(character) {
+            character
+        } else {
+            ' '
+        };
+    }
+
+    /// Prints the text starting at the cell, the text is clipped at the end of the row.
+    ///
+    /// The wide characters take two cells.
+    pub fn print(&mut self, column: u16, row: u16, text: &str) {
+        let mut column = column as usize;
+
+        for character in text.chars() {
+            let width = if is_wide(character) { 2 } else { 1 };
+
+            if column + width > self.columns as usize {
+                break;
+            }
+
+            self.set(column as u16, row, character);
+            column += width;
+        }
+    }
+
+    /// Writes the cells changed since the last flush and flushes the writer.
+    ///
+    /// The changed cells of a row are written in runs, the cursor is moved (`CUP`) to
+    /// the start of every run. Unchanged cells between two runs are written again if
+    /// it's shorter than the cursor movement. The cursor is left after the last written
+    /// cell.
+    pub fn flush(&mut self, writer: &mut impl Write) -> Result<()> {
+        let mut output = String::new();
+        // The cursor position if it's known (it's unknown after the last column)
+        let mut cursor = None;
+
+        for row in 0..self.rows {
+            let mut column = 0;
+
+            while column < self.columns {
+                if !self.is_changed(column, row) {
+                    column += 1;
+                    continue;
+                }
+
+                let start = column;
+                let end = self.run_end(start, row);
+
+                if cursor != Some((start, row)) {
+                    output.push_str(&move_to(start, row));
+                }
+
+                let from = self.index(start, row).unwrap();
+                output.extend(
+                    self.cells[from..from + (end - start) as usize]
+                        .iter()
+                        .filter(|character| **character != CONTINUATION),
+                );
+
+                cursor = if end < self.columns {
+                    Some((end, row))
+                } else {
+                    None
+                };
+                column = end;
+            }
+        }
+
+        writer.write_all(output.as_bytes())?;
+        writer.flush()?;
+
+        // Keep it last - remember the frame only if we were able to write it
+        self.set_flushed();
+
+        Ok(())
+    }
+
+    // Returns the cells of the frame being drawn (row by row)
+    #[cfg(windows)]
+    pub(crate) fn cells(&self) -> &[char] {
+        &self.cells
+    }
+
+    // Remembers the frame being drawn as the flushed one (the captured frame is shared)
+    pub(crate) fn set_flushed(&mut self) {
+        let frame = Arc::new(self.cells.clone());
+
+        if self.captured {
+            snapshot::record_frame(self.columns, Arc::clone(&frame));
+        }
+
+        self.flushed = Some(frame);
+    }
+
+    // Returns the end (exclusive) of the run of changed cells starting at the column, the
+    // gaps of unchanged cells are included if they're shorter than the cursor movement (in
+    // columns). The run never ends between the cells of a wide character.
+    fn run_end(&self, start: u16, row: u16) -> u16 {
+        let mut end = self.cell_end(start, row);
+
+        loop {
+            let mut next = end;
+
+            while next < self.columns && !self.is_changed(next, row) {
+                next += 1;
+            }
+
+            if next == self.columns {
+                return end;
+            }
+
+            if (next - end) as usize > move_to(next, row).len() {
+                return end;
+            }
+
+            end = self.cell_end(next, row);
+        }
+    }
+
+    // Returns the end (exclusive) of the character starting at the column, the wide
+    // characters take two cells
+    fn cell_end(&self, column: u16, row: u16) -> u16 {
+        match self.index(column + 1, row) {
+            Some(index) if self.cells[index] == CONTINUATION => column + 2,
+            _ => column + 1,
+        }
+    }
+
+    // Replaces the wide character the cell is part of with spaces
+    fn release(&mut self, index: usize) {
+        if self.cells[index] == CONTINUATION {
+            self.cells[index - 1] = ' ';
+        } else if self.cells.get(index + 1) == Some(&CONTINUATION) {
+            self.cells[index + 1] = ' ';
+        }
+
+        self.cells[index] = ' ';
+    }
+
+    // Checks whether the cell differs from the last flushed frame
+    fn is_changed(&self, column: u16, row: u16) -> bool {
+        let index = match self.index(column, row) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        match &self.flushed {
+            Some(flushed) => flushed[index] != self.cells[index],
+            None => true,
+        }
+    }
+
+    fn index(&self, column: u16, row: u16) -> Option<usize> {
+        if column < self.columns && row < self.rows {
+            Some(row as usize * self.columns as usize + column as usize)
+        } else {
+            None
+        }
+    }
+}
+
+// Checks whether the character takes two columns
+fn is_wide(character: char) -> bool {
+    let code = character as u32;
+
+    WIDE_RANGES
+        .iter()
+        .any(|(first, last)| *first <= code && code <= *last)
+}
+
+// Returns the sequence moving the cursor to the cell (`CUP`)
+fn move_to(column: u16, row: u16) -> String {
+    format!(csi!("{};{}H"), row + 1, column + 1)
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+#[cfg(windows)]
+use crossterm_utils::supports_ansi;
+use crossterm_utils::{ErrorKind, Result};
+#[cfg(windows)]
+use crossterm_winapi::ScreenBuffer;
+
+use crate::alternate::ansi::{CLEAR_SCREEN_CSI_SEQUENCE, MOVE_TO_ORIGIN_CSI_SEQUENCE};
+use crate::alternate::{self, Flavor, Output};
+use crate::ScreenError;
+
+// The maximum size of the snapshot of a buffer, the oldest lines are dropped
+const SNAPSHOT_LIMIT: usize = 1 << 20;
+
+/// An identifier of a screen buffer created by the
+/// [`ScreenBufferManager`](struct.ScreenBufferManager.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScreenBufferId(usize);
+
+impl ScreenBufferId {
+    /// The main screen buffer.
+    pub const MAIN: ScreenBufferId = ScreenBufferId(0);
+}
+
+/// A manager of several screen buffers.
+///
+/// It creates, switches between, and drops several screen buffers. The legacy Windows
+/// console (no ANSI support) creates a real console screen buffer for every buffer.
+/// Terminals have the main screen and one alternate screen only. All the buffers
+/// share the alternate screen there and the manager keeps a snapshot of everything
+/// written to every buffer (via the [`write`](#method.write) method). The snapshot
+/// is replayed when the buffer is activated. The snapshot is truncated whenever the
+/// buffer is cleared (`CSI 2 J`) and its oldest lines are dropped when it grows over
+/// 1 MiB. The snapshot is dropped with the buffer when it's closed.
+///
+/// The buffers can't be activated while the alternate screen is held by an
+/// [`AlternateScreen`](struct.AlternateScreen.html) value, the
+/// [`ScreenError::AlternateScreenInUse`](enum.ScreenError.html#variant.AlternateScreenInUse)
+/// error is returned.
+///
+/// The main screen buffer
+/// ([`ScreenBufferId::MAIN`](struct.ScreenBufferId.html#associatedconstant.MAIN)) is
+/// activated when the manager is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{ScreenBufferId, ScreenBufferManager};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let mut manager = ScreenBufferManager::new();
+///
+///     let editor = manager.create()?;
+///     let help = manager.create()?;
+///
+///     manager.activate(editor)?;
+///     manager.write(editor, b"Editor")?;
+///     manager.write(help, b"Help")?;
+///
+///     manager.activate(help)?;
+///     manager.close(editor)?;
+///
+///     manager.activate(ScreenBufferId::MAIN)
+/// }
+/// ```
+pub struct ScreenBufferManager {
+    buffers: HashMap<ScreenBufferId, Buffer>,
+    next_id: usize,
+    active: ScreenBufferId,
+}
+
+struct Buffer {
+    // Everything written since the buffer was cleared (ANSI only)
+    snapshot: Vec<u8>,
+    #[cfg(windows)]
+    console: Option<ScreenBuffer>,
+}
+
+impl ScreenBufferManager {
+    /// Creates a manager with the main screen buffer only.
+    pub fn new() -> ScreenBufferManager {
+        ScreenBufferManager {
+            buffers: HashMap::new(),
+            next_id: 1,
+            active: ScreenBufferId::MAIN,
+        }
+    }
+
+    /// Creates a new screen buffer.
+    ///
+    /// The buffer is not activated.
+    pub fn create(&mut self) -> Result<ScreenBufferId> {
+        let buffer = Buffer {
+            snapshot: Vec::new(),
+            #[cfg(windows)]
+            console: if supports_ansi() {
+                None
+            } else {
+                let console = ScreenBuffer::create();
+                let info = alternate::windows::main_screen_buffer_info()?;
+                alternate::windows::fit_to_window(*console.handle(), &info)?;
+                Some(console)
+            },
+        };
+
+        let id = ScreenBufferId(self.next_id);
+        self.next_id += 1;
+        self.buffers.insert(id, buffer);
+
+        Ok(id)
+    }
+
+    /// Returns the active screen buffer.
+    pub fn active(&self) -> ScreenBufferId {
+        self.active
+    }
+
+    /// Activates (displays) the screen buffer.
+    pub fn activate(&mut self, id: ScreenBufferId) -> Result<()> {
+        if id == self.active {
+            return Ok(());
+        }
+
+        if id == ScreenBufferId::MAIN {
+            alternate::leave_nested(|| {
+                alternate::alternate_screen(Output::Stdout, Flavor::Cleared).leave()
+            })?;
+            self.active = id;
+            return Ok(());
+        }
+
+        let buffer = self.buffers.get(&id).ok_or_else(|| unknown_buffer(id))?;
+
+        // Do not take over the alternate screen of somebody else
+        if self.active == ScreenBufferId::MAIN && alternate::is_active() {
+            return Err(ScreenError::AlternateScreenInUse.into());
+        }
+
+        #[cfg(windows)]
+        {
+            if let Some(console) = &buffer.console {
+                console.show()?;
+                if self.active == ScreenBufferId::MAIN {
+                    alternate::enter_nested(|| {
+                        alternate::set_active(true, Output::Stdout);
+                        Ok(())
+                    })?;
+                }
+                self.active = id;
+                return Ok(());
+            }
+        }
+
+        if self.active == ScreenBufferId::MAIN {
+            alternate::enter_nested(|| {
+                alternate::alternate_screen(Output::Stdout, Flavor::Cleared).enter()
+            })?;
+        }
+        self.active = id;
+
+        // Replay the snapshot of the buffer
+        let mut stdout = io::stdout();
+        stdout.write_all(CLEAR_SCREEN_CSI_SEQUENCE.as_bytes())?;
+        stdout.write_all(MOVE_TO_ORIGIN_CSI_SEQUENCE.as_bytes())?;
+        stdout.write_all(&buffer.snapshot)?;
+        stdout.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes the data to the screen buffer.
+    ///
+    /// The data is written to the terminal if the buffer is active, it's kept in the
+    /// snapshot of the buffer (ANSI) or written to the console screen buffer (legacy
+    /// Windows) otherwise. Use the standard output to write to the main screen buffer.
+    pub fn write(&mut self, id: ScreenBufferId, data: &[u8]) -> Result<()> {
+        let active = id == self.active;
+        let buffer = self
+            .buffers
+            .get_mut(&id)
+            .ok_or_else(|| unknown_buffer(id))?;
+
+        #[cfg(windows)]
+        {
+            if let Some(console) = &buffer.console {
+                return write_console(console, data);
+            }
+        }
+
+        let clear = CLEAR_SCREEN_CSI_SEQUENCE.as_bytes();
+        match data
+            .windows(clear.len())
+            .rposition(|window| window == clear)
+        {
+            Some(position) => {
+                buffer.snapshot.clear();
+                buffer.snapshot.extend_from_slice(&data[position..]);
+            }
+            None => buffer.snapshot.extend_from_slice(data),
+        }
+        truncate_snapshot(&mut buffer.snapshot);
+
+        if active {
+            let mut stdout = io::stdout();
+            stdout.write_all(data)?;
+            stdout.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes (drops) the screen buffer.
+    ///
+    /// The main screen buffer is activated if the buffer is active.
+    pub fn close(&mut self, id: ScreenBufferId) -> Result<()> {
+        if !self.buffers.contains_key(&id) {
+            return Err(unknown_buffer(id));
+        }
+
+        if id == self.active {
+            self.activate(ScreenBufferId::MAIN)?;
+        }
+
+        self.buffers.remove(&id);
+        Ok(())
+    }
+}
+
+impl Default for ScreenBufferManager {
+    fn default() -> Self {
+        ScreenBufferManager::new()
+    }
+}
+
+impl Drop for ScreenBufferManager {
+    fn drop(&mut self) {
+        let _ = self.activate(ScreenBufferId::MAIN);
+    }
+}
+
+// Drops the oldest lines of the snapshot over the limit, the escape sequences are never
+// split (they don't contain the line feeds)
+fn truncate_snapshot(snapshot: &mut Vec<u8>) {
+    if snapshot.len() <= SNAPSHOT_LIMIT {
+        return;
+    }
+
+    let excess = snapshot.len() - SNAPSHOT_LIMIT;
+    match snapshot[excess..].iter().position(|byte| *byte == b'\n') {
+        Some(position) => {
+            snapshot.drain(..excess + position + 1);
+        }
+        None => snapshot.clear(),
+    }
+}
+
+fn unknown_buffer(id: ScreenBufferId) -> ErrorKind {
+    ErrorKind::IoError(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("Unknown screen buffer {:?}", id),
+    ))
+}
+
+#[cfg(windows)]
+fn write_console(console: &ScreenBuffer, data: &[u8]) -> Result<()> {
+    use winapi::um::consoleapi::WriteConsoleW;
+
+    let text: Vec<u16> = String::from_utf8_lossy(data).encode_utf16().collect();
+    let mut written = 0;
+
+    let result = unsafe {
+        WriteConsoleW(
+            *console.handle(),
+            text.as_ptr() as *const _,
+            text.len() as u32,
+            &mut written,
+            std::ptr::null_mut(),
+        )
+    };
+
+    if result == 0 {
+        return Err(ErrorKind::IoError(io::Error::last_os_error()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::env;
+
+#[cfg(unix)]
+use crossterm_utils::Result;
+
+use crate::DumbTerminalPolicy;
+
+// Terminals known to support the synchronized update (`?2026`), `$TERM_PROGRAM` or a
+// part of `$TERM`
+const SYNCHRONIZED_UPDATE_TERMINALS: &[&str] = &[
+    "WezTerm",
+    "iTerm.app",
+    "contour",
+    "foot",
+    "kitty",
+    "alacritty",
+];
+
+// Terminals known to support the bracketed paste (`?2004`), a prefix of `$TERM`
+const BRACKETED_PASTE_TERMINALS: &[&str] = &[
+    "xterm",
+    "rxvt",
+    "screen",
+    "tmux",
+    "alacritty",
+    "kitty",
+    "foot",
+    "wezterm",
+    "contour",
+    "st-",
+    "vte",
+    "gnome",
+    "konsole",
+];
+
+// The private modes asked for by `query_capabilities`
+#[cfg(unix)]
+const QUERIED_MODES: [u16; 3] = [1049, 2026, 2004];
+
+/// The features supported by the terminal.
+///
+/// See the [`capabilities`](fn.capabilities.html) and the
+/// [`query_capabilities`](fn.query_capabilities.html) functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TerminalCapabilities {
+    /// The alternate screen (`?1049`, the console screen buffers on Windows).
+    pub alternate_screen: bool,
+    /// The synchronized update (`?2026`).
+    pub synchronized_update: bool,
+    /// The bracketed paste (`?2004`).
+    pub bracketed_paste: bool,
+    /// The scroll regions (`DECSTBM`, emulated on the legacy Windows console).
+    pub scroll_region: bool,
+}
+
+/// Detects the features supported by the terminal.
+///
+/// It's a heuristic, the terminal is not asked (see the
+/// [`query_capabilities`](fn.query_capabilities.html) function). `$TERM`, the terminfo
+/// entry (`smcup`, `rmcup`, and `csr`), `$TERM_PROGRAM`, `$COLORTERM` & `$TMUX` are
+/// consulted on UNIX. The console type (legacy, virtual terminal sequences, pseudo
+/// console) and `$WT_SESSION` are consulted on Windows. Nothing is supported by the dumb
+/// terminals (see [`DumbTerminalPolicy`](enum.DumbTerminalPolicy.html)).
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::capabilities;
+///
+/// if capabilities().synchronized_update {
+///     // Render the frames in the synchronized update
+/// }
+/// ```
+pub fn capabilities() -> TerminalCapabilities {
+    if DumbTerminalPolicy::is_dumb_terminal() {
+        return TerminalCapabilities {
+            alternate_screen: false,
+            synchronized_update: false,
+            bracketed_paste: false,
+            scroll_region: false,
+        };
+    }
+
+    platform_capabilities()
+}
+
+/// Detects the features supported by the terminal, the terminal is asked.
+///
+/// The heuristic of the [`capabilities`](fn.capabilities.html) function is refined by
+/// the terminal answers, the state of the `?1049`, `?2026` & `?2004` modes is requested
+/// (`DECRQM`) together with the primary device attributes (`DA1`). The raw mode is
+/// enabled for the time of the query and the response is awaited for up to 100ms. The
+/// heuristic is returned if the terminal doesn't respond. Input typed at the same time
+/// can be consumed.
+///
+/// UNIX only.
+#[cfg(unix)]
+pub fn query_capabilities() -> Result<TerminalCapabilities> {
+    let mut capabilities = capabilities();
+
+    if DumbTerminalPolicy::is_dumb_terminal() {
+        return Ok(capabilities);
+    }
+
+    let states = match crate::query::query_modes(&QUERIED_MODES)? {
+        Some(states) => states,
+        None => return Ok(capabilities),
+    };
+
+    // The terminal answered, only the modes it doesn't recognize are unsupported
+    // (the terminals without `DECRQM` support keep the heuristic)
+    if states.iter().any(|state| state.is_recognized()) {
+        capabilities.alternate_screen = states[0].is_recognized();
+        capabilities.synchronized_update = states[1].is_recognized();
+        capabilities.bracketed_paste = states[2].is_recognized();
+    }
+
+    Ok(capabilities)
+}
+
+#[cfg(unix)]
+fn platform_capabilities() -> TerminalCapabilities {
+    let term = env::var("TERM").unwrap_or_default();
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+    // Modern terminals (with the true color support) implement the bracketed paste
+    let modern = env::var_os("COLORTERM").is_some() || env::var_os("TMUX").is_some();
+
+    TerminalCapabilities {
+        alternate_screen: crate::alternate::is_supported(),
+        synchronized_update: SYNCHRONIZED_UPDATE_TERMINALS
+            .iter()
+            .any(|terminal| program == *terminal || term.contains(&terminal.to_lowercase())),
+        bracketed_paste: modern
+            || BRACKETED_PASTE_TERMINALS
+                .iter()
+                .any(|terminal| term.starts_with(terminal)),
+        // Terminals without the terminfo entry are assumed to support it
+        scroll_region: crate::alternate::terminfo::has_scroll_region().unwrap_or(true),
+    }
+}
+
+#[cfg(windows)]
+fn platform_capabilities() -> TerminalCapabilities {
+    // The Windows Terminal (and the Cygwin/MSYS terminals) interpret the sequences
+    let windows_terminal = env::var_os("WT_SESSION").is_some();
+    let ansi = crate::alternate::prefers_ansi();
+    let msys = crate::sys::msys::is_msys_output();
+
+    TerminalCapabilities {
+        alternate_screen: crate::alternate::is_supported(),
+        synchronized_update: ansi && windows_terminal,
+        bracketed_paste: ansi && (windows_terminal || msys),
+        // The legacy console scrolls the console screen buffer
+        scroll_region: true,
+    }
+}
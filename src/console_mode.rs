@@ -0,0 +1,195 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub};
+
+use winapi::shared::minwindef::DWORD;
+use winapi::um::wincon::{
+    ENABLE_ECHO_INPUT, ENABLE_EXTENDED_FLAGS, ENABLE_INSERT_MODE, ENABLE_LINE_INPUT,
+    ENABLE_MOUSE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_QUICK_EDIT_MODE,
+    ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_WINDOW_INPUT,
+};
+
+/// A set of the console input mode flags.
+///
+/// It's used to pick exactly which console input modes are cleared when the raw
+/// mode is enabled. See the
+/// [`RawScreen::into_raw_mode_with_mask`](struct.RawScreen.html#method.into_raw_mode_with_mask)
+/// method.
+///
+/// Windows only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{ConsoleInputModeFlags, RawScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let mask = ConsoleInputModeFlags::ECHO_INPUT | ConsoleInputModeFlags::LINE_INPUT;
+///     let _raw = RawScreen::into_raw_mode_with_mask(mask)?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ConsoleInputModeFlags(DWORD);
+
+impl ConsoleInputModeFlags {
+    /// `ENABLE_PROCESSED_INPUT`
+    pub const PROCESSED_INPUT: ConsoleInputModeFlags =
+        ConsoleInputModeFlags(ENABLE_PROCESSED_INPUT);
+    /// `ENABLE_LINE_INPUT`
+    pub const LINE_INPUT: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_LINE_INPUT);
+    /// `ENABLE_ECHO_INPUT`
+    pub const ECHO_INPUT: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_ECHO_INPUT);
+    /// `ENABLE_WINDOW_INPUT`
+    pub const WINDOW_INPUT: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_WINDOW_INPUT);
+    /// `ENABLE_MOUSE_INPUT`
+    pub const MOUSE_INPUT: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_MOUSE_INPUT);
+    /// `ENABLE_INSERT_MODE`
+    pub const INSERT_MODE: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_INSERT_MODE);
+    /// `ENABLE_QUICK_EDIT_MODE`
+    pub const QUICK_EDIT_MODE: ConsoleInputModeFlags =
+        ConsoleInputModeFlags(ENABLE_QUICK_EDIT_MODE);
+    /// `ENABLE_EXTENDED_FLAGS`
+    pub const EXTENDED_FLAGS: ConsoleInputModeFlags = ConsoleInputModeFlags(ENABLE_EXTENDED_FLAGS);
+    /// `ENABLE_VIRTUAL_TERMINAL_INPUT`
+    pub const VIRTUAL_TERMINAL_INPUT: ConsoleInputModeFlags =
+        ConsoleInputModeFlags(ENABLE_VIRTUAL_TERMINAL_INPUT);
+
+    // All the named flags
+    const NAMED: [(&'static str, ConsoleInputModeFlags); 9] = [
+        ("PROCESSED_INPUT", ConsoleInputModeFlags::PROCESSED_INPUT),
+        ("LINE_INPUT", ConsoleInputModeFlags::LINE_INPUT),
+        ("ECHO_INPUT", ConsoleInputModeFlags::ECHO_INPUT),
+        ("WINDOW_INPUT", ConsoleInputModeFlags::WINDOW_INPUT),
+        ("MOUSE_INPUT", ConsoleInputModeFlags::MOUSE_INPUT),
+        ("INSERT_MODE", ConsoleInputModeFlags::INSERT_MODE),
+        ("QUICK_EDIT_MODE", ConsoleInputModeFlags::QUICK_EDIT_MODE),
+        ("EXTENDED_FLAGS", ConsoleInputModeFlags::EXTENDED_FLAGS),
+        (
+            "VIRTUAL_TERMINAL_INPUT",
+            ConsoleInputModeFlags::VIRTUAL_TERMINAL_INPUT,
+        ),
+    ];
+
+    /// Returns an empty set of flags.
+    pub const fn empty() -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(0)
+    }
+
+    /// Returns the flags cleared by the default raw mode (line, echo, and processed input).
+    pub const fn raw_mode() -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT)
+    }
+
+    /// Creates flags from the raw console mode bits, unknown bits are dropped.
+    pub fn from_bits_truncate(bits: DWORD) -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(bits & ConsoleInputModeFlags::all().0)
+    }
+
+    /// Returns all the known flags.
+    pub fn all() -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags::NAMED
+            .iter()
+            .fold(ConsoleInputModeFlags::empty(), |all, (_, flag)| all | *flag)
+    }
+
+    /// Returns the raw console mode bits.
+    pub fn bits(&self) -> DWORD {
+        self.0
+    }
+
+    /// Checks whether no flag is set.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checks whether all the `other` flags are set.
+    pub fn contains(&self, other: ConsoleInputModeFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Checks whether any of the `other` flags is set.
+    pub fn intersects(&self, other: ConsoleInputModeFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Sets the `other` flags.
+    pub fn insert(&mut self, other: ConsoleInputModeFlags) {
+        self.0 |= other.0;
+    }
+
+    /// Clears the `other` flags.
+    pub fn remove(&mut self, other: ConsoleInputModeFlags) {
+        self.0 &= !other.0;
+    }
+
+    /// Sets or clears the `other` flags.
+    pub fn set(&mut self, other: ConsoleInputModeFlags, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+}
+
+impl Debug for ConsoleInputModeFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = ConsoleInputModeFlags::NAMED
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(name, _)| *name)
+            .collect();
+
+        if names.is_empty() {
+            write!(f, "(empty)")
+        } else {
+            write!(f, "{}", names.join(" | "))
+        }
+    }
+}
+
+impl BitOr for ConsoleInputModeFlags {
+    type Output = ConsoleInputModeFlags;
+
+    fn bitor(self, rhs: ConsoleInputModeFlags) -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ConsoleInputModeFlags {
+    fn bitor_assign(&mut self, rhs: ConsoleInputModeFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for ConsoleInputModeFlags {
+    type Output = ConsoleInputModeFlags;
+
+    fn bitand(self, rhs: ConsoleInputModeFlags) -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for ConsoleInputModeFlags {
+    fn bitand_assign(&mut self, rhs: ConsoleInputModeFlags) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for ConsoleInputModeFlags {
+    type Output = ConsoleInputModeFlags;
+
+    fn sub(self, rhs: ConsoleInputModeFlags) -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags(self.0 & !rhs.0)
+    }
+}
+
+impl Not for ConsoleInputModeFlags {
+    type Output = ConsoleInputModeFlags;
+
+    fn not(self) -> ConsoleInputModeFlags {
+        ConsoleInputModeFlags::all() - self
+    }
+}
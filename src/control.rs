@@ -0,0 +1,263 @@
+#[cfg(windows)]
+use crossterm_utils::Result;
+use crossterm_utils::{csi, Command};
+
+use crate::dumb;
+
+/// A command to fill the screen with `E`s (`DECALN`).
+///
+/// It's useful to diagnose the terminal geometry (like the scroll regions or the
+/// column switching). The cursor is moved to the origin and the scroll region is reset.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) fills the visible window with `E`s
+/// (`FillConsoleOutputCharacter`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, ScreenAlignmentTest};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), ScreenAlignmentTest)
+/// }
+/// ```
+pub struct ScreenAlignmentTest;
+
+impl Command for ScreenAlignmentTest {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb("\x1B#8")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::fill_window('E')
+    }
+}
+
+/// A command to reset the terminal modes to the defaults (`DECSTR`).
+///
+/// The soft reset resets the modes (like the scroll region, the origin mode, the
+/// insert mode, the cursor visibility, and the text attributes), but it keeps the screen
+/// contents. It's an escape hatch for apps recovering from a corrupted terminal state.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) resets the console output mode, the
+/// text attributes, and the cursor visibility. The console input mode (the raw mode) is
+/// untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, SoftReset};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SoftReset)
+/// }
+/// ```
+pub struct SoftReset;
+
+impl Command for SoftReset {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("!p"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::reset(false)
+    }
+}
+
+/// A command to reset the terminal to the initial state (`RIS`).
+///
+/// The hard reset resets everything the [`SoftReset`](struct.SoftReset.html) command
+/// does, clears the screen, and moves the cursor to the origin. Some terminals clear
+/// the scrollback as well.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) clears the visible window. The console
+/// input mode (the raw mode) is untouched.
+pub struct HardReset;
+
+impl Command for HardReset {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb("\x1Bc")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::reset(true)
+    }
+}
+
+/// A command to resize the terminal window (`XTWINOPS 8`).
+///
+/// The first value is the number of columns and the second value is the number of rows.
+/// Resize the window before the alternate screen is entered, the legacy Windows
+/// alternate screen buffer is sized to the window.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Some terminals ignore it (or allow it only if it's enabled in the settings). The legacy
+/// Windows console (no ANSI support) resizes the screen buffer
+/// (`SetConsoleScreenBufferSize`) and the window (`SetConsoleWindowInfo`), the
+/// scrollback is kept.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, EnterAlternateScreen, SetScreenSize};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SetScreenSize(80, 24), EnterAlternateScreen)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetScreenSize(pub u16, pub u16);
+
+impl Command for SetScreenSize {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(format!(csi!("8;{};{}t"), self.1, self.0))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::set_size(self.0, self.1)
+    }
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::mem;
+
+    use crossterm_utils::Result;
+    use crossterm_winapi::{ConsoleMode, Handle};
+    use winapi::shared::minwindef::TRUE;
+    use winapi::um::wincon::{
+        FillConsoleOutputAttribute, FillConsoleOutputCharacterW, GetConsoleCursorInfo,
+        GetConsoleScreenBufferInfo, SetConsoleCursorInfo, SetConsoleCursorPosition,
+        SetConsoleScreenBufferSize, SetConsoleTextAttribute, SetConsoleWindowInfo,
+        CONSOLE_CURSOR_INFO, CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_PROCESSED_OUTPUT,
+        ENABLE_WRAP_AT_EOL_OUTPUT, FOREGROUND_BLUE, FOREGROUND_GREEN, FOREGROUND_RED, SMALL_RECT,
+    };
+    use winapi::um::winnt::HANDLE;
+
+    use crate::alternate::windows::alternate_screen_buffer;
+    use crate::sys::winapi::result;
+
+    // The default text attributes (gray on black)
+    const DEFAULT_ATTRIBUTES: u16 = FOREGROUND_RED | FOREGROUND_GREEN | FOREGROUND_BLUE;
+
+    // Resets the console output mode, the text attributes, and the cursor visibility of the
+    // active screen buffer, clears the visible window as well if `clear` is `true`
+    pub(super) fn reset(clear: bool) -> Result<()> {
+        let (handle, _) = screen_buffer()?;
+
+        ConsoleMode::from(handle).set_mode(ENABLE_PROCESSED_OUTPUT | ENABLE_WRAP_AT_EOL_OUTPUT)?;
+        result(unsafe { SetConsoleTextAttribute(handle, DEFAULT_ATTRIBUTES) })?;
+
+        let mut cursor: CONSOLE_CURSOR_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleCursorInfo(handle, &mut cursor) })?;
+        cursor.bVisible = TRUE;
+        result(unsafe { SetConsoleCursorInfo(handle, &cursor) })?;
+
+        if clear {
+            fill_window(' ')?;
+        }
+
+        Ok(())
+    }
+
+    // Fills the visible window of the active screen buffer with the character (and the
+    // current text attributes) and moves the cursor to the origin of the window
+    pub(super) fn fill_window(character: char) -> Result<()> {
+        let (handle, info) = screen_buffer()?;
+
+        let origin = COORD {
+            X: 0,
+            Y: info.srWindow.Top,
+        };
+        let length = info.dwSize.X as u32 * (info.srWindow.Bottom - info.srWindow.Top + 1) as u32;
+
+        let mut written = 0;
+        result(unsafe {
+            FillConsoleOutputCharacterW(handle, character as u16, length, origin, &mut written)
+        })?;
+        result(unsafe {
+            FillConsoleOutputAttribute(handle, info.wAttributes, length, origin, &mut written)
+        })?;
+
+        let window_origin = COORD {
+            X: info.srWindow.Left,
+            Y: info.srWindow.Top,
+        };
+        result(unsafe { SetConsoleCursorPosition(handle, window_origin) })
+    }
+
+    // Resizes the window of the active screen buffer, the buffer is resized to the window
+    // width and it keeps its height (the scrollback) if it's higher than the window
+    pub(super) fn set_size(columns: u16, rows: u16) -> Result<()> {
+        let (handle, info) = screen_buffer()?;
+
+        let columns = columns as i16;
+        let rows = rows as i16;
+
+        // The window must always fit into the buffer - grow the buffer first, resize the
+        // window, and shrink the buffer to the window width then
+        let grown = COORD {
+            X: info.dwSize.X.max(columns),
+            Y: info.dwSize.Y.max(rows),
+        };
+        result(unsafe { SetConsoleScreenBufferSize(handle, grown) })?;
+
+        let top = info.srWindow.Top.min(grown.Y - rows);
+        let window = SMALL_RECT {
+            Left: 0,
+            Top: top,
+            Right: columns - 1,
+            Bottom: top + rows - 1,
+        };
+        result(unsafe { SetConsoleWindowInfo(handle, TRUE, &window) })?;
+
+        let size = COORD {
+            X: columns,
+            Y: grown.Y,
+        };
+        result(unsafe { SetConsoleScreenBufferSize(handle, size) })
+    }
+
+    // Returns the active screen buffer (the alternate one if it's active) and its info
+    fn screen_buffer() -> Result<(HANDLE, CONSOLE_SCREEN_BUFFER_INFO)> {
+        let handle = match alternate_screen_buffer() {
+            Some(handle) => handle,
+            None => Handle::output_handle()?,
+        };
+
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+
+        Ok((handle, info))
+    }
+}
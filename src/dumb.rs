@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm_utils::Result;
+
+use crate::ScreenError;
+
+// The policy applied to the dumb terminals
+static DUMB_TERMINAL_POLICY: AtomicU8 = AtomicU8::new(NOOP);
+
+const NOOP: u8 = 0;
+const ERROR: u8 = 1;
+const EMIT: u8 = 2;
+
+/// The behavior of the alternate screen & mode commands on a dumb terminal.
+///
+/// A terminal is dumb if `$TERM` is `dumb` or it's not set at all (like the output
+/// captured by an editor, a build tool, or a CI job), see the
+/// [`is_dumb_terminal`](#method.is_dumb_terminal) method. The escape sequences would be
+/// printed as garbage into the logs there.
+///
+/// The raw mode is not affected. Windows consoles are never considered to be dumb.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{AlternateScreen, DumbTerminalPolicy, ScreenError};
+///
+/// DumbTerminalPolicy::set(DumbTerminalPolicy::Error);
+///
+/// match AlternateScreen::to_alternate(true) {
+///     Err(e) if ScreenError::from_error_kind(&e) == Some(ScreenError::DumbTerminal) => {
+///         println!("Falling back to the line-based output");
+///     }
+///     _ => {}
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DumbTerminalPolicy {
+    /// The alternate screen & mode commands write nothing (the default).
+    Noop,
+    /// Like `Noop`, but the alternate screen functions (like
+    /// [`AlternateScreen::to_alternate`](struct.AlternateScreen.html#method.to_alternate))
+    /// fail with the [`ScreenError::DumbTerminal`](enum.ScreenError.html#variant.DumbTerminal)
+    /// error. The commands can't fail, they write nothing.
+    Error,
+    /// The escape sequences are written anyway.
+    Emit,
+}
+
+impl DumbTerminalPolicy {
+    /// Sets the policy applied to the dumb terminals.
+    pub fn set(policy: DumbTerminalPolicy) {
+        let value = match policy {
+            DumbTerminalPolicy::Noop => NOOP,
+            DumbTerminalPolicy::Error => ERROR,
+            DumbTerminalPolicy::Emit => EMIT,
+        };
+
+        DUMB_TERMINAL_POLICY.store(value, Ordering::SeqCst);
+    }
+
+    /// Returns the policy applied to the dumb terminals.
+    pub fn current() -> DumbTerminalPolicy {
+        match DUMB_TERMINAL_POLICY.load(Ordering::SeqCst) {
+            ERROR => DumbTerminalPolicy::Error,
+            EMIT => DumbTerminalPolicy::Emit,
+            _ => DumbTerminalPolicy::Noop,
+        }
+    }
+
+    /// Checks whether the terminal is dumb (`$TERM` is `dumb` or it's not set).
+    ///
+    /// Always `false` on Windows.
+    #[cfg(unix)]
+    pub fn is_dumb_terminal() -> bool {
+        match std::env::var_os("TERM") {
+            Some(term) => term.is_empty() || term == "dumb",
+            None => true,
+        }
+    }
+
+    /// Checks whether the terminal is dumb (`$TERM` is `dumb` or it's not set).
+    ///
+    /// Always `false` on Windows.
+    #[cfg(windows)]
+    pub fn is_dumb_terminal() -> bool {
+        false
+    }
+}
+
+// Checks whether the sequences must not be written (the terminal is dumb and the
+// policy is not `Emit`)
+pub(crate) fn suppressed() -> bool {
+    DumbTerminalPolicy::current() != DumbTerminalPolicy::Emit
+        && DumbTerminalPolicy::is_dumb_terminal()
+}
+
+// Fails with the `ScreenError::DumbTerminal` error if the terminal is dumb and the
+// policy is `Error`
+pub(crate) fn check() -> Result<()> {
+    if DumbTerminalPolicy::current() == DumbTerminalPolicy::Error
+        && DumbTerminalPolicy::is_dumb_terminal()
+    {
+        return Err(ScreenError::DumbTerminal.into());
+    }
+
+    Ok(())
+}
+
+// Returns the sequence, or nothing if the terminal is dumb (see `suppressed`)
+pub(crate) fn unless_dumb<T: Default>(sequence: T) -> T {
+    if suppressed() {
+        T::default()
+    } else {
+        sequence
+    }
+}
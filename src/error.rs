@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use crossterm_utils::ErrorKind;
+
+/// Errors specific to the screen functionality.
+///
+/// The [`ErrorKind`](enum.ErrorKind.html) type is shared by all the crossterm crates and
+/// can't be extended. These errors are wrapped in the `ErrorKind::IoError` variant, use
+/// the [`from_error_kind`](enum.ScreenError.html#method.from_error_kind) method to
+/// extract them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{RawModeOptions, RawScreen, ScreenError};
+///
+/// let _raw = RawScreen::into_raw_mode();
+///
+/// match RawScreen::into_raw_mode_with(RawModeOptions::raw().exclusive(true)) {
+///     Err(e) if ScreenError::from_error_kind(&e) == Some(ScreenError::AlreadyRaw) => {
+///         println!("Somebody else owns the raw mode");
+///     }
+///     _ => {}
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScreenError {
+    /// The raw mode is already enabled.
+    AlreadyRaw,
+    /// The raw mode is already enabled with different options.
+    ///
+    /// It's returned when a mode (like the cbreak mode) is requested while another one
+    /// (like the raw mode) is held by a `RawScreen` value.
+    ModeMismatch,
+    /// There's no terminal (or the given file is not a terminal).
+    ///
+    /// On UNIX, it's returned when all the standard streams are redirected and there's
+    /// no controlling terminal either (like in a CI job). On Windows, it's returned when
+    /// the handle is not a console.
+    NotATerminal,
+    /// The terminal is dumb (`$TERM` is `dumb` or it's not set).
+    ///
+    /// It's returned by the alternate screen functions if the
+    /// [`DumbTerminalPolicy::Error`](enum.DumbTerminalPolicy.html#variant.Error) policy
+    /// is set.
+    DumbTerminal,
+    /// The alternate screen is already held by an `AlternateScreen` value.
+    ///
+    /// It's returned when a screen buffer of the
+    /// [`ScreenBufferManager`](struct.ScreenBufferManager.html) is activated while the
+    /// alternate screen is active, the buffer would overwrite its contents.
+    AlternateScreenInUse,
+}
+
+impl ScreenError {
+    /// Extracts the screen error from the given error.
+    ///
+    /// Returns `None` if the error is not a screen error.
+    pub fn from_error_kind(error: &ErrorKind) -> Option<ScreenError> {
+        match error {
+            ErrorKind::IoError(e) => e
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<ScreenError>())
+                .copied(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScreenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenError::AlreadyRaw => write!(f, "The raw mode is already enabled"),
+            ScreenError::ModeMismatch => {
+                write!(f, "The raw mode is already enabled with different options")
+            }
+            ScreenError::NotATerminal => write!(f, "Not a terminal"),
+            ScreenError::DumbTerminal => write!(f, "The terminal is dumb"),
+            ScreenError::AlternateScreenInUse => {
+                write!(f, "The alternate screen is already active")
+            }
+        }
+    }
+}
+
+impl Error for ScreenError {}
+
+impl From<ScreenError> for ErrorKind {
+    fn from(error: ScreenError) -> Self {
+        ErrorKind::IoError(io::Error::other(error))
+    }
+}
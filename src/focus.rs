@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use crossterm_utils::{csi, write_cout, Command, ExecutableCommand, Result};
+
+use crate::{dumb, restore};
+
+const FOCUS_CHANGE_STATE_CHANGE: &str = "focus change";
+
+static ENABLE_FOCUS_CHANGE_CSI_SEQUENCE: &str = csi!("?1004h");
+static DISABLE_FOCUS_CHANGE_CSI_SEQUENCE: &str = csi!("?1004l");
+
+/// A command to enable the focus change reporting (`?1004h`).
+///
+/// The terminal reports `CSI I` when the window gains the focus and `CSI O` when it
+/// loses it. Full-screen apps can pause the rendering while they're not focused. The
+/// reporting is disabled when the alternate screen is left. The reporting enabled via the
+/// [`execute_tracked`](#method.execute_tracked) method is disabled by the
+/// [`restore_all`](fn.restore_all.html) function as well.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support), the focus events are
+/// always reported there (`FOCUS_EVENT`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableFocusChange, EnableFocusChange};
+///
+/// fn main() -> Result<()> {
+///     EnableFocusChange.execute_tracked(&mut stdout())?;
+///
+///     // Read the focus events
+///
+///     DisableFocusChange.execute_tracked(&mut stdout())
+/// }
+/// ```
+pub struct EnableFocusChange;
+
+impl EnableFocusChange {
+    /// Enables the focus change reporting and tracks it for the
+    /// [`restore_all`](fn.restore_all.html) function.
+    ///
+    /// The reporting is tracked only if the sequence was written.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(EnableFocusChange)?;
+
+        // Keep it last - track the reporting only if we were able to enable it
+        if !dumb::suppressed() {
+            track();
+        }
+
+        Ok(())
+    }
+}
+
+impl Command for EnableFocusChange {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(ENABLE_FOCUS_CHANGE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to disable the focus change reporting (`?1004l`).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct DisableFocusChange;
+
+impl DisableFocusChange {
+    /// Disables the focus change reporting enabled by the
+    /// [`EnableFocusChange::execute_tracked`](struct.EnableFocusChange.html#method.execute_tracked)
+    /// method.
+    ///
+    /// The reporting is forgotten only if the sequence was written.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(DisableFocusChange)?;
+
+        // Keep it last - forget the reporting only if we were able to disable it
+        untrack();
+        Ok(())
+    }
+}
+
+impl Command for DisableFocusChange {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(DISABLE_FOCUS_CHANGE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Tracks the enabled focus change reporting for the `restore_all` function
+pub(crate) fn track() {
+    restore::register(FOCUS_CHANGE_STATE_CHANGE, disable);
+}
+
+// Forgets the disabled focus change reporting
+pub(crate) fn untrack() {
+    restore::unregister(FOCUS_CHANGE_STATE_CHANGE);
+}
+
+// Disables the focus change reporting (the restore function)
+fn disable() -> Result<()> {
+    untrack();
+
+    // There's nothing to disable on the legacy Windows console
+    #[cfg(windows)]
+    {
+        if !crossterm_utils::supports_ansi() {
+            return Ok(());
+        }
+    }
+
+    write_cout!(DISABLE_FOCUS_CHANGE_CSI_SEQUENCE)?;
+    Ok(())
+}
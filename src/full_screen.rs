@@ -0,0 +1,111 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+use crossterm_utils::{ExecutableCommand, Result};
+use lazy_static::lazy_static;
+
+use crate::{EnterAlternateScreen, LeaveAlternateScreen, RawScreen};
+
+lazy_static! {
+    // The raw mode enabled by the `EnterFullScreen`
+    static ref FULL_SCREEN_RAW_MODE: Mutex<Option<RawScreen>> = Mutex::new(None);
+}
+
+/// Switches to the alternate screen and enables the raw mode.
+///
+/// The raw mode stays enabled until the [`LeaveFullScreen`](struct.LeaveFullScreen.html)
+/// is executed.
+///
+/// # Notes
+///
+/// It's not a command, the raw mode can't be enabled while a command is written. Execute
+/// it with the [`execute_on`](#method.execute_on) method, the raw mode is enabled once the
+/// alternate screen sequence is written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use crossterm_screen::{EnterFullScreen, LeaveFullScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     EnterFullScreen::raw().execute_on(&mut stdout())?;
+///
+///     // Do anything on the alternate screen in the raw mode
+///
+///     LeaveFullScreen.execute_on(&mut stdout())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnterFullScreen {
+    /// `true` enables the raw mode as well.
+    pub raw: bool,
+}
+
+impl EnterFullScreen {
+    /// Creates a full screen switch which enables the raw mode as well.
+    pub fn raw() -> EnterFullScreen {
+        EnterFullScreen { raw: true }
+    }
+
+    /// Creates a full screen switch which keeps the cooked mode.
+    pub fn cooked() -> EnterFullScreen {
+        EnterFullScreen { raw: false }
+    }
+
+    /// Switches to the alternate screen (the sequence is written to the writer) and
+    /// enables the raw mode.
+    ///
+    /// The raw mode is enabled only if the sequence was written. You'll be switched back
+    /// to the main screen if the raw mode can't be enabled.
+    pub fn execute_on<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(EnterAlternateScreen)?;
+
+        if self.raw {
+            if let Err(e) = enable_raw_mode() {
+                let _ = writer.execute(LeaveAlternateScreen);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Disables the raw mode and switches back to the main screen.
+///
+/// It reverts the [`EnterFullScreen`](struct.EnterFullScreen.html), see its documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaveFullScreen;
+
+impl LeaveFullScreen {
+    /// Disables the raw mode and switches back to the main screen (the sequence is
+    /// written to the writer).
+    pub fn execute_on<W: Write>(&self, writer: &mut W) -> Result<()> {
+        disable_raw_mode();
+        writer.execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+}
+
+// Enables the raw mode (noop if it's enabled by the `EnterFullScreen` already)
+fn enable_raw_mode() -> Result<()> {
+    let mut raw_mode = FULL_SCREEN_RAW_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if raw_mode.is_none() {
+        *raw_mode = Some(RawScreen::into_raw_mode()?);
+    }
+
+    Ok(())
+}
+
+// Disables the raw mode enabled by the `EnterFullScreen`
+fn disable_raw_mode() {
+    FULL_SCREEN_RAW_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+}
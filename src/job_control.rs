@@ -0,0 +1,213 @@
+//! Job control (`SIGTSTP` & `SIGCONT`) handling.
+//!
+//! Signal handlers are very limited in what they can do (no locks, no allocations, ...).
+//! The self-pipe trick is used - handlers write a byte to a pipe and a background thread
+//! does the real work (switches terminal modes, stops the process, ...).
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::{mem, ptr, thread};
+
+use crossterm_utils::Result;
+use lazy_static::lazy_static;
+use libc::{c_int, c_void, SA_RESTART, SIGCONT, SIGSTOP, SIGTSTP};
+
+use crate::alternate;
+use crate::sys;
+use crate::sys::unix::wrap_with_result;
+
+const SUSPEND: u8 = b'z';
+const RESUME: u8 = b'c';
+
+// Write end of the pipe, -1 if the handlers are not installed
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+lazy_static! {
+    static ref HANDLERS: Mutex<Handlers> = Mutex::new(Handlers {
+        users: 0,
+        previous: Vec::new(),
+    });
+}
+
+struct Handlers {
+    // Number of the `RawScreen` values which opted in
+    users: usize,
+    // The actions prior the handlers were installed
+    previous: Vec<(c_int, libc::sigaction)>,
+}
+
+// Locks the handlers, a poisoned lock is recovered (the handlers are always consistent)
+fn lock_handlers() -> MutexGuard<'static, Handlers> {
+    HANDLERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Installs the `SIGTSTP` & `SIGCONT` handlers (once per the opted-in `RawScreen` values).
+pub(crate) fn install() -> Result<()> {
+    let mut handlers = lock_handlers();
+
+    if handlers.users > 0 {
+        handlers.users += 1;
+        return Ok(());
+    }
+
+    let mut fds = [0 as RawFd; 2];
+    wrap_with_result(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    // Never block in the signal handler
+    if let Err(error) =
+        wrap_with_result(unsafe { libc::fcntl(write_fd, libc::F_SETFL, libc::O_NONBLOCK) })
+    {
+        close_pipe(read_fd, write_fd);
+        return Err(error);
+    }
+
+    SIGNAL_PIPE.store(write_fd, Ordering::SeqCst);
+
+    // The signals received before the thread is spawned wait in the pipe
+    for signal in &[SIGTSTP, SIGCONT] {
+        match set_handler(*signal) {
+            Ok(previous) => handlers.previous.push((*signal, previous)),
+            Err(error) => {
+                restore_handlers(&mut handlers);
+                close_pipe(read_fd, write_fd);
+                return Err(error);
+            }
+        }
+    }
+
+    let spawned = thread::Builder::new()
+        .name("crossterm-job-control".to_string())
+        .spawn(move || handle_signals(read_fd));
+
+    if let Err(error) = spawned {
+        restore_handlers(&mut handlers);
+        close_pipe(read_fd, write_fd);
+        return Err(error.into());
+    }
+
+    // Keep it last - count the user only if we were able to install all of them
+    handlers.users = 1;
+
+    Ok(())
+}
+
+/// Restores the `SIGTSTP` & `SIGCONT` actions prior the handlers when the last opted-in
+/// `RawScreen` value is dropped.
+pub(crate) fn uninstall() {
+    let mut handlers = lock_handlers();
+
+    if handlers.users == 0 {
+        return;
+    }
+
+    handlers.users -= 1;
+
+    if handlers.users == 0 {
+        restore_handlers(&mut handlers);
+
+        // The thread stops when the write end is closed (it closes the read end)
+        let write_fd = SIGNAL_PIPE.swap(-1, Ordering::SeqCst);
+        unsafe {
+            libc::close(write_fd);
+        }
+    }
+}
+
+// Installs the handler of the signal, returns the previous action
+fn set_handler(signal: c_int) -> Result<libc::sigaction> {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = on_signal as extern "C" fn(c_int) as libc::sighandler_t;
+        action.sa_flags = SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+
+        let mut previous: libc::sigaction = mem::zeroed();
+        wrap_with_result(libc::sigaction(signal, &action, &mut previous))?;
+        Ok(previous)
+    }
+}
+
+// Restores the actions prior the handlers (the errors are ignored, there's nothing to do
+// about them)
+fn restore_handlers(handlers: &mut Handlers) {
+    for (signal, previous) in handlers.previous.drain(..) {
+        unsafe {
+            libc::sigaction(signal, &previous, ptr::null_mut());
+        }
+    }
+}
+
+// Closes the pipe which was not handed over to the thread
+fn close_pipe(read_fd: RawFd, write_fd: RawFd) {
+    SIGNAL_PIPE.store(-1, Ordering::SeqCst);
+
+    unsafe {
+        libc::close(read_fd);
+        libc::close(write_fd);
+    }
+}
+
+extern "C" fn on_signal(signal: c_int) {
+    let byte = if signal == SIGTSTP { SUSPEND } else { RESUME };
+    let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+
+    // `write` is async-signal-safe, the byte is dropped if the pipe is full
+    unsafe {
+        libc::write(fd, &byte as *const u8 as *const c_void, 1);
+    }
+}
+
+fn handle_signals(read_fd: RawFd) {
+    let mut byte = 0u8;
+
+    loop {
+        let read = unsafe { libc::read(read_fd, &mut byte as *mut u8 as *mut c_void, 1) };
+
+        match read {
+            1 if byte == SUSPEND => suspend(),
+            1 => resume(),
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            _ => break,
+        }
+    }
+
+    unsafe {
+        libc::close(read_fd);
+    }
+}
+
+fn suspend() {
+    if alternate::is_active() {
+        // Do not mark the alternate screen as inactive, it's entered again on resume
+        let _ = alternate::ansi::write_sequence(
+            alternate::active_output(),
+            &alternate::ansi::leave_sequence(),
+        );
+    }
+
+    if let Ok(command) = sys::RawModeCommand::new() {
+        let _ = command.suspend();
+    }
+
+    // Stop the whole process, `SIGCONT` is delivered when it's resumed
+    unsafe {
+        libc::kill(libc::getpid(), SIGSTOP);
+    }
+}
+
+fn resume() {
+    if let Ok(command) = sys::RawModeCommand::new() {
+        let _ = command.resume();
+    }
+
+    if alternate::is_active() {
+        // Enter the very same alternate screen (cleared, preserved, or composed) again
+        let _ = alternate::ansi::write_sequence(
+            alternate::active_output(),
+            &alternate::ansi::enter_sequence(alternate::flavor()),
+        );
+    }
+}
@@ -22,8 +22,9 @@
 //! displayed on the screen.
 //!
 //! Crossterm allows you to switch between those buffers; the screen you are working in is called the
-//! 'main screen'. We call the other screen the 'alternate screen'. One note to take is that crossterm
-//! does not support the creation and switching between several buffers.
+//! 'main screen'. We call the other screen the 'alternate screen'. Use the
+//! [`ScreenBufferManager`](struct.ScreenBufferManager.html) to create and switch between several
+//! buffers.
 //!
 //! ### Alternate Screen
 //!
@@ -53,6 +54,13 @@
 //! - Special keys like backspace and CTL+C will not be processed by terminal driver
 //! - New line character will not be processed therefore `println!` can't be used, use `write!` instead
 
+use std::borrow::Cow;
+use std::io::{self, StdoutLock, Write};
+#[cfg(windows)]
+use std::os::windows::io::RawHandle;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
 // This brings the trait into scope, so we're able to call enter()/leave(),
 // but it it's false positive for unused_imports check
 #[allow(unused_imports)]
@@ -62,11 +70,95 @@ pub use crossterm_utils::{
     execute, queue, Command, ErrorKind, ExecutableCommand, QueueableCommand, Result,
 };
 
-pub use self::raw::{IntoRawMode, RawScreen};
+pub use self::alternate::AlternateScreenBuilder;
+pub use self::attributes::{AttributeChange, TerminalAttributes};
+pub use self::buffer::VirtualScreen;
+pub use self::buffers::{ScreenBufferId, ScreenBufferManager};
+#[cfg(unix)]
+pub use self::capabilities::query_capabilities;
+pub use self::capabilities::{capabilities, TerminalCapabilities};
+#[cfg(windows)]
+pub use self::console_mode::ConsoleInputModeFlags;
+pub use self::control::{HardReset, ScreenAlignmentTest, SetScreenSize, SoftReset};
+pub use self::dumb::DumbTerminalPolicy;
+pub use self::error::ScreenError;
+pub use self::focus::{DisableFocusChange, EnableFocusChange};
+pub use self::full_screen::{EnterFullScreen, LeaveFullScreen};
+pub use self::modes::{
+    DisableApplicationKeypad, DisableInsertMode, DisableLineWrap, DisableOriginMode,
+    DisableReverseScreen, EnableApplicationKeypad, EnableInsertMode, EnableLineWrap,
+    EnableOriginMode, EnableReverseScreen, RestoreModes, SaveModes, SetColumns132, SetColumns80,
+};
+pub use self::mouse::{
+    DisableAlternateScroll, DisableMouseCapture, EnableAlternateScroll, EnableMouseCapture,
+    EnableMouseTracking, MouseTracking,
+};
+pub use self::passthrough::Passthrough;
+pub use self::presenter::Presenter;
+#[cfg(unix)]
+pub use self::query::{query_mode, window_position, ModeState};
+pub use self::raw::{
+    disable_echo, enable_echo, IntoRawMode, NoEcho, NoFlowControl, RawModeOptions, RawScreen,
+    RawTerminal, TerminalMode,
+};
+#[cfg(feature = "recording")]
+pub use self::recording::{RecordedEvent, RecordingScreen};
+pub use self::resize::resize_events;
+pub use self::restore::{
+    install_restore_panic_hook, reset_screen_state, restore_all, restore_on_exit, ResetScreenState,
+};
+pub use self::scroll_region::{
+    DisableLeftRightMargins, EnableLeftRightMargins, ResetScrollRegion, ScrollRegionDown,
+    ScrollRegionUp, SetLeftRightMargins, SetScrollRegion,
+};
+pub use self::session::{TerminalSession, TerminalSessionBuilder};
+pub use self::size::size;
+#[cfg(unix)]
+pub use self::size::size_pixels;
+pub use self::snapshot::ScreenSnapshot;
+pub use self::synchronized::{BeginSynchronizedUpdate, EndSynchronizedUpdate, SynchronizedUpdate};
+#[cfg(all(windows, feature = "recording"))]
+pub use self::sys::{set_console_backend, ConsoleBackend, FakeConsole};
+pub use self::title::{PopTitle, PushTitle};
+pub use self::tty::{is_tty, StdStream};
+pub use self::window::XtWindowOp;
 
 mod alternate;
+pub mod ansi;
+mod attributes;
+mod buffer;
+mod buffers;
+mod capabilities;
+#[cfg(windows)]
+mod console_mode;
+mod control;
+mod dumb;
+mod error;
+mod focus;
+mod full_screen;
+#[cfg(unix)]
+mod job_control;
+mod modes;
+mod mouse;
+mod passthrough;
+mod presenter;
+#[cfg(unix)]
+mod query;
 mod raw;
+#[cfg(feature = "recording")]
+mod recording;
+mod resize;
+mod restore;
+mod scroll_region;
+mod session;
+mod size;
+mod snapshot;
+mod synchronized;
 mod sys;
+mod termux;
+mod title;
+mod tty;
+mod window;
 
 /// An alternate screen.
 ///
@@ -76,6 +168,12 @@ mod sys;
 /// Be aware that you'll be switched back to the main screen when you drop the
 /// `AlternateScreen` value.
 ///
+/// The alternate screen can be nested (a library and the application both enter it).
+/// The nesting depth is tracked globally (including the commands executed with the
+/// [`EnterAlternateScreen::execute_tracked`](struct.EnterAlternateScreen.html#method.execute_tracked)
+/// and [`LeaveAlternateScreen::execute_tracked`](struct.LeaveAlternateScreen.html#method.execute_tracked)
+/// methods) and the main screen is shown when the outermost alternate screen is left.
+///
 /// It's recommended to use the command API. See the
 /// [`EnterAlternateScreen`](struct.EnterAlternateScreen.html)
 /// and [`LeaveAlternateScreen`](struct.LeaveAlternateScreen.html)
@@ -98,11 +196,17 @@ mod sys;
 /// } // `_alternate` dropped here <- raw mode disabled & back to main screen
 /// ```
 pub struct AlternateScreen {
-    #[cfg(windows)]
-    alternate: Box<(dyn alternate::AlternateScreen + Sync + Send)>,
-    #[cfg(unix)]
-    alternate: alternate::AnsiAlternateScreen,
+    // Keep it first - fields are dropped in the declaration order, the raw mode must be
+    // disabled before the main screen is shown (the restore output is not mangled)
     raw_screen: Option<RawScreen>,
+    alternate: Box<dyn alternate::AlternateScreen + Sync + Send>,
+    // `false` if the alternate screen is not tracked by this crate (arbitrary writer)
+    nested: bool,
+    // Features enabled together with the alternate screen
+    features: alternate::Features,
+    // `false` once this value switched back to the main screen
+    entered: AtomicBool,
+    leave_on_drop: bool,
 }
 
 impl AlternateScreen {
@@ -117,14 +221,137 @@ impl AlternateScreen {
     /// You'll be automatically switched to the main screen if this function
     /// fails.
     pub fn to_alternate(raw_mode: bool) -> Result<AlternateScreen> {
-        let alternate = alternate::alternate_screen();
+        AlternateScreen::enter(alternate::Output::Stdout, raw_mode)
+    }
+
+    /// Switches to the alternate screen, the sequences are written to the standard error.
+    ///
+    /// It allows to run an interactive UI when the standard output is redirected
+    /// (like `mytool | tee log`). The raw mode is applied to the terminal the process
+    /// is attached to, see the [`RawScreen`](struct.RawScreen.html) documentation.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_mode` - `true` enables the raw mode as well
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::{stderr, Write};
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let _alternate = AlternateScreen::to_alternate_stderr(true)?;
+    ///
+    ///     write!(stderr(), "Interactive UI")?;
+    ///     println!("Output for the pipe");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_alternate_stderr(raw_mode: bool) -> Result<AlternateScreen> {
+        AlternateScreen::enter(alternate::Output::Stderr, raw_mode)
+    }
+
+    /// Switches to the alternate screen on the standard error.
+    ///
+    /// It's an alias of the [`to_alternate_stderr`](#method.to_alternate_stderr) method
+    /// for tools which write the machine-readable output to the standard output (like
+    /// `fzf`) and render the UI on the standard error.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_mode` - `true` enables the raw mode as well
+    pub fn on_stderr(raw_mode: bool) -> Result<AlternateScreen> {
+        AlternateScreen::to_alternate_stderr(raw_mode)
+    }
+
+    /// Switches to the alternate screen, the sequences are written to the given writer.
+    ///
+    /// The leave sequence is written to the same writer when the main screen is shown
+    /// again. It's useful when the terminal is not the one the process is attached to
+    /// (like a PTY or an SSH session). The alternate screen is not tracked by this crate,
+    /// it's neither nested nor restored by the [`restore_all`](fn.restore_all.html)
+    /// function.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - the writer the sequences are written to
+    /// * `raw_mode` - `true` enables the raw mode of the terminal the process is attached to
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::TcpStream;
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let stream = TcpStream::connect("127.0.0.1:2323")?;
+    ///     let _alternate = AlternateScreen::to_alternate_on(stream.try_clone()?, false)?;
+    ///
+    ///     // Render the UI to the `stream`
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_alternate_on<W>(writer: W, raw_mode: bool) -> Result<AlternateScreen>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut alternate: Box<dyn alternate::AlternateScreen + Sync + Send> =
+            Box::new(alternate::WriterAlternateScreen::new(Box::new(writer)));
+
+        if alternate::is_disabled() {
+            alternate = Box::new(alternate::DisabledAlternateScreen::new(alternate, false));
+        }
+
         alternate.enter()?;
 
+        AlternateScreen::new(alternate, false, alternate::Features::default(), raw_mode)
+    }
+
+    /// Creates a builder to switch to the alternate screen with bundled options.
+    ///
+    /// See the [`AlternateScreenBuilder`](struct.AlternateScreenBuilder.html)
+    /// documentation for more information.
+    pub fn builder() -> AlternateScreenBuilder {
+        AlternateScreenBuilder::new()
+    }
+
+    fn enter(output: alternate::Output, raw_mode: bool) -> Result<AlternateScreen> {
+        dumb::check()?;
+
+        let alternate = alternate::alternate_screen(output, alternate::Flavor::Cleared);
+        alternate::enter_nested(|| alternate.enter())?;
+
+        AlternateScreen::new(alternate, true, alternate::Features::default(), raw_mode)
+    }
+
+    // Creates the alternate screen which was entered already, enables the features
+    // and the raw mode
+    pub(crate) fn new(
+        alternate: Box<dyn alternate::AlternateScreen + Sync + Send>,
+        nested: bool,
+        features: alternate::Features,
+        raw_mode: bool,
+    ) -> Result<AlternateScreen> {
         let mut alternate = AlternateScreen {
-            alternate,
             raw_screen: None,
+            alternate,
+            nested,
+            features,
+            entered: AtomicBool::new(true),
+            leave_on_drop: true,
         };
 
+        // If it fails, `alternate` will be dropped and we'll switch back to the main screen
+        let sequence = features.enable_sequence();
+        if !sequence.is_empty() {
+            alternate.alternate.write(&sequence)?;
+        }
+
         if raw_mode {
             // If into_raw_mode fails, `alternate` will be dropped and
             // we'll switch back to the main screen.
@@ -134,24 +361,501 @@ impl AlternateScreen {
         Ok(alternate)
     }
 
+    /// Checks whether the alternate screen was entered (and not left) via this crate.
+    ///
+    /// It includes the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command.
+    /// Use the [`query_active`](struct.AlternateScreen.html#method.query_active) method
+    /// to ask the terminal.
+    pub fn is_active() -> bool {
+        alternate::is_active()
+    }
+
+    /// Checks whether the alternate screen will actually work.
+    ///
+    /// On UNIX, `$TERM` must be set (and not `dumb`) and the terminfo entry (if there's
+    /// any) must declare the `smcup` & `rmcup` capabilities. On Windows, the console must
+    /// support the virtual terminal sequences or the standard output must be a console
+    /// (the console screen buffers are switched).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    ///
+    /// if AlternateScreen::is_supported() {
+    ///     let _alternate = AlternateScreen::to_alternate(false);
+    /// }
+    /// ```
+    pub fn is_supported() -> bool {
+        alternate::is_supported()
+    }
+
+    /// Disables the alternate screen for the rest of the process.
+    ///
+    /// All the alternate screen switches (including the
+    /// [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command) become noops,
+    /// the raw mode and the [`builder`](#method.builder) features still work. The
+    /// alternate screen which is active already is still left. Setting the
+    /// `CROSSTERM_NO_ALT_SCREEN` environment variable (to anything but `0`) has the
+    /// same effect. It keeps the output in the scrollback (CI logs, screen readers, ...).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     if std::env::args().any(|arg| arg == "--no-alt-screen") {
+    ///         AlternateScreen::force_disabled();
+    ///     }
+    ///
+    ///     // Stays on the main screen, the raw mode is enabled
+    ///     let _alternate = AlternateScreen::to_alternate(true)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn force_disabled() {
+        alternate::force_disable();
+    }
+
+    /// Substitutes custom sequences to enter & leave the alternate screen.
+    ///
+    /// The sequences are used instead of `?1049h` & `?1049l` (or the terminfo ones, see the
+    /// `terminfo` feature) for the default (cleared) alternate screen, including the
+    /// [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) &
+    /// [`LeaveAlternateScreen`](struct.LeaveAlternateScreen.html) commands. The modes are
+    /// still reset before the leave sequence. It's useful for proprietary terminal emulators
+    /// or test harnesses.
+    ///
+    /// The preserved & composed flavors and the
+    /// [`to_alternate_on`](#method.to_alternate_on) writers keep their sequences. Change
+    /// them before the alternate screen is entered, the active one is left with the new
+    /// leave sequence. The legacy Windows console (no ANSI support) ignores them.
+    ///
+    /// # Arguments
+    ///
+    /// * `enter` - the sequence to enter the alternate screen
+    /// * `leave` - the sequence to leave the alternate screen
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     AlternateScreen::set_sequences("<enter>", "<leave>");
+    ///
+    ///     // Writes `<enter>`
+    ///     let _alternate = AlternateScreen::to_alternate(false)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_sequences(enter: &'static str, leave: &'static str) {
+        alternate::ansi::set_custom_sequences(Some((enter, leave)));
+    }
+
+    /// Restores the default sequences to enter & leave the alternate screen.
+    ///
+    /// See the [`set_sequences`](#method.set_sequences) method for more information.
+    pub fn reset_sequences() {
+        alternate::ansi::set_custom_sequences(None);
+    }
+
+    /// Forces the composed alternate screen sequences in place of `?1049h` & `?1049l`.
+    ///
+    /// GNU screen (and old tmux versions) handle `?1049h` poorly. The default (cleared)
+    /// alternate screen is entered as the composed one (`?1048h` + `?47h` + clear, see the
+    /// [`EnterComposedAlternateScreen`](struct.EnterComposedAlternateScreen.html)
+    /// command) if `$TERM` starts with `screen`. `Some(true)` always uses the composed
+    /// sequences, `Some(false)` never does, and `None` detects it from `$TERM` again. The
+    /// detection is skipped if the custom sequences are set, see the
+    /// [`set_sequences`](#method.set_sequences) method.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     // The multiplexer handles `?1049h` well
+    ///     AlternateScreen::force_composed(Some(false));
+    ///
+    ///     let _alternate = AlternateScreen::to_alternate(true)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn force_composed(composed: Option<bool>) {
+        alternate::force_composed(composed);
+    }
+
+    /// Checks whether the alternate screen is disabled.
+    ///
+    /// See the [`force_disabled`](#method.force_disabled) method. It's disabled on the dumb
+    /// terminals as well, see the [`DumbTerminalPolicy`](enum.DumbTerminalPolicy.html)
+    /// documentation.
+    pub fn is_disabled() -> bool {
+        alternate::is_disabled()
+    }
+
+    /// Asks the terminal whether the alternate screen is active (`DECRQM ?1049`).
+    ///
+    /// It's a best-effort query. The raw mode is enabled for the time of the query and
+    /// the response is awaited for up to 100ms. `None` is returned if the terminal doesn't
+    /// support the query. Input typed at the same time can be consumed.
+    ///
+    /// UNIX only.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let active = AlternateScreen::query_active()?.unwrap_or_else(AlternateScreen::is_active);
+    ///
+    ///     if !active {
+    ///         let _alternate = AlternateScreen::to_alternate(true)?;
+    ///         // Do something on the alternate screen
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(unix)]
+    pub fn query_active() -> Result<Option<bool>> {
+        let state = query_mode(1049)?;
+
+        if state.is_recognized() {
+            Ok(Some(state.is_set()))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Switches to the main screen.
+    ///
+    /// The main screen is shown only if it's the outermost alternate screen, see the
+    /// [`AlternateScreen`](struct.AlternateScreen.html) documentation. The modes set on
+    /// the alternate screen (the scroll region, the origin mode, the line wrap, the mouse
+    /// capture, the bracketed paste, the focus reporting, and the application keypad) are
+    /// reset before.
     pub fn to_main(&self) -> Result<()> {
-        self.alternate.leave()
+        if self.entered.swap(false, Ordering::SeqCst) {
+            let sequence = self.features.disable_sequence();
+            if !sequence.is_empty() {
+                let _ = self.alternate.write(&sequence);
+            }
+
+            let result = if self.nested {
+                alternate::leave_nested(|| self.alternate.leave())
+            } else {
+                self.alternate.leave()
+            };
+
+            if let Err(e) = result {
+                self.entered.store(true, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the handle of the console screen buffer the alternate screen is displayed in.
+    ///
+    /// The legacy Windows console (no ANSI support) creates a new screen buffer for the
+    /// alternate screen, use this handle with the console API (like `WriteConsoleOutput`)
+    /// to write to it. The standard output handle is returned otherwise.
+    ///
+    /// Windows only.
+    #[cfg(windows)]
+    pub fn console_buffer(&self) -> Result<RawHandle> {
+        match alternate::windows::alternate_screen_buffer() {
+            Some(handle) => Ok(handle as RawHandle),
+            None => Ok(crossterm_winapi::Handle::output_handle()? as RawHandle),
+        }
+    }
+
+    /// Switches back to the alternate screen after the [`to_main`](#method.to_main) call.
+    ///
+    /// The alternate screen is entered the very same way as before (including the
+    /// features of the [`builder`](#method.builder)). The raw mode is untouched, it's
+    /// kept enabled by the [`to_main`](#method.to_main) method. It's a noop if the
+    /// alternate screen is shown already.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let alternate = AlternateScreen::to_alternate(false)?;
+    ///
+    ///     alternate.to_main()?;
+    ///     Command::new("git").arg("log").status()?;
+    ///     alternate.to_alternate_again()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_alternate_again(&self) -> Result<()> {
+        if self.entered.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let result = if self.nested {
+            alternate::enter_nested(|| self.alternate.enter())
+        } else {
+            self.alternate.enter()
+        };
+
+        if let Err(e) = result {
+            self.entered.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+
+        let sequence = self.features.enable_sequence();
+        if !sequence.is_empty() {
+            self.alternate.write(&sequence)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replays the snapshot of the main screen onto the alternate screen.
+    ///
+    /// The alternate screen is cleared and the snapshot is written from the origin. It's
+    /// a noop if the alternate screen is disabled (the main screen is shown already).
+    /// See the [`ScreenSnapshot`](struct.ScreenSnapshot.html) documentation.
+    pub fn replay(&self, snapshot: &ScreenSnapshot) -> Result<()> {
+        if alternate::is_disabled() {
+            return Ok(());
+        }
+
+        #[cfg(windows)]
+        {
+            if alternate::windows::alternate_screen_buffer().is_some() {
+                return alternate::windows::write_to_alternate_screen_buffer(&snapshot.text());
+            }
+        }
+
+        self.alternate.write(&format!(
+            "{}{}{}",
+            alternate::ansi::CLEAR_SCREEN_CSI_SEQUENCE,
+            alternate::ansi::MOVE_TO_ORIGIN_CSI_SEQUENCE,
+            snapshot.text()
+        ))
+    }
+
+    /// Captures the visible contents of the alternate screen (text only).
+    ///
+    /// The alternate screen buffer is read back (`ReadConsoleOutput`) on the legacy
+    /// Windows console. Terminals can't be asked for their contents, the last frame
+    /// written by a [`VirtualScreen`](struct.VirtualScreen.html) (or a
+    /// [`Presenter`](struct.Presenter.html)) capturing its frames since the alternate
+    /// screen was entered is returned otherwise, the snapshot is empty if there's none
+    /// (see the [`VirtualScreen::capture_frames`](struct.VirtualScreen.html#method.capture_frames)
+    /// method). The trailing spaces of every line are trimmed.
+    ///
+    /// # Examples
+    ///
+    /// Print the final frame to the main screen on exit (like some pagers do):
+    ///
+    /// ```no_run
+    /// use std::io::stdout;
+    /// use crossterm_screen::{AlternateScreen, Presenter};
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let alternate = AlternateScreen::to_alternate(false)?;
+    ///
+    ///     let mut presenter = Presenter::new(stdout(), 80, 24);
+    ///     presenter.capture_frames(true);
+    ///     presenter.frame().print(0, 0, "The final frame");
+    ///     presenter.present()?;
+    ///
+    ///     let snapshot = alternate.capture()?;
+    ///     drop(alternate);
+    ///
+    ///     for line in snapshot.lines() {
+    ///         println!("{}", line);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn capture(&self) -> Result<ScreenSnapshot> {
+        #[cfg(windows)]
+        {
+            if let Some(handle) = alternate::windows::alternate_screen_buffer() {
+                return Ok(ScreenSnapshot::from_lines(
+                    alternate::windows::read_screen_lines(handle)?,
+                ));
+            }
+        }
+
+        Ok(ScreenSnapshot::from_shadow())
+    }
+
+    /// Keeps the alternate screen when `self` is dropped.
+    ///
+    /// It allows to hand off the responsibility for leaving the alternate screen (like
+    /// when you exec another full-screen program). The raw mode (if enabled) is still
+    /// disabled. The alternate screen is still left by the [`restore_all`](fn.restore_all.html)
+    /// function.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::AlternateScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut alternate = AlternateScreen::to_alternate(false)?;
+    ///     alternate.keep_alternate_on_drop();
+    ///
+    ///     Ok(())
+    /// } // `alternate` is dropped here <- still on the alternate screen
+    /// ```
+    pub fn keep_alternate_on_drop(&mut self) {
+        self.leave_on_drop = false;
     }
 }
 
 impl Drop for AlternateScreen {
     fn drop(&mut self) {
-        let _ = self.to_main();
+        // Revert in the reverse order - the raw mode, the features, the alternate screen.
+        // Explicitly, `Drop::drop` runs before the fields are dropped.
+        drop(self.raw_screen.take());
+
+        if self.leave_on_drop {
+            let _ = self.to_main();
+        }
+    }
+}
+
+/// Runs the given closure on the alternate screen.
+///
+/// The alternate screen (and the raw mode if `raw_mode` is `true`) is entered before
+/// the closure is called. The closure gets the locked standard output. The output is
+/// flushed and the main screen is shown when the closure returns, even if it panics
+/// (the panic is propagated after the main screen is shown). There's no guard which
+/// could be dropped too early.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::Write;
+/// use crossterm_screen::with_alternate_screen;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let answer = with_alternate_screen(true, |stdout| {
+///         write!(stdout, "Do something on the alternate screen in the raw mode")?;
+///         Ok(42)
+///     })?;
+///
+///     // Back on the main screen here
+///
+///     Ok(())
+/// }
+/// ```
+pub fn with_alternate_screen<T, F>(raw_mode: bool, f: F) -> Result<T>
+where
+    F: FnOnce(&mut StdoutLock<'_>) -> Result<T>,
+{
+    let alternate = AlternateScreen::to_alternate(raw_mode)?;
+
+    let stdout = io::stdout();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut lock = stdout.lock();
+        let result = f(&mut lock);
+        lock.flush()?;
+        result
+    }));
+
+    drop(alternate);
+
+    match result {
+        Ok(value) => value,
+        Err(payload) => panic::resume_unwind(payload),
     }
 }
 
+/// Forces (`true`) or forbids (`false`) the ANSI alternate screen.
+///
+/// The ANSI alternate screen is used if the console supports the virtual terminal
+/// sequences, the console screen buffers are switched otherwise. Some terminals are
+/// misdetected (like Cygwin/Mintty or old Windows versions with ANSICON), pick the
+/// backend explicitly there. Setting the `CROSSTERM_FORCE_ANSI` environment variable to
+/// `1` or `0` has the same effect, this function takes precedence.
+///
+/// The commands (like [`EnterAlternateScreen`](struct.EnterAlternateScreen.html)) can be
+/// forced to the ANSI backend only, they're written as ANSI whenever the console
+/// supports the sequences. It has no effect on UNIX, the ANSI alternate screen is always used.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::AlternateScreen;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     // Mintty interprets the sequences, but it's not a console
+///     crossterm_screen::force_ansi(true);
+///
+///     let _alternate = AlternateScreen::to_alternate(true)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn force_ansi(ansi: bool) {
+    alternate::force_ansi(ansi);
+}
+
 /// A command to switch to the alternate screen.
 ///
+/// There are three flavors of the alternate screen:
+///
+/// * cleared (`?1049h`, this command) - the cursor is saved and the alternate screen
+///   is cleared
+/// * preserved (`?47h`, the
+///   [`EnterPreservedAlternateScreen`](struct.EnterPreservedAlternateScreen.html)
+///   command) - the cursor is not saved and the alternate screen is not cleared
+/// * composed (`?1048h` + `?47h`, the
+///   [`EnterComposedAlternateScreen`](struct.EnterComposedAlternateScreen.html) command) -
+///   the cleared one composed from the separate modes, for terminals which mishandle
+///   `?1049h` (some older `screen` & `tmux` versions)
+///
+/// Some terminals and pagers behave better with one or the other.
+///
 /// # Notes
 ///
 /// Commands must be executed/queued for execution otherwise they do nothing.
 ///
+/// The command only writes the sequence, the nesting depth and the
+/// [`restore_all`](fn.restore_all.html) function are not aware of it. Use the
+/// [`execute_tracked`](#method.execute_tracked) method (or the
+/// [`AlternateScreen`](struct.AlternateScreen.html) value) to track the alternate screen.
+///
+/// The legacy Windows console (no ANSI support) always creates a new screen buffer, it's
+/// tracked.
+///
+/// The command can be executed against any writer, like `stderr()`, when the standard
+/// output is reserved for the machine-readable output.
+///
+/// It's a noop if the alternate screen is disabled, see the
+/// [`AlternateScreen::force_disabled`](struct.AlternateScreen.html#method.force_disabled)
+/// method.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -168,26 +872,115 @@ impl Drop for AlternateScreen {
 /// ```
 pub struct EnterAlternateScreen;
 
+impl EnterAlternateScreen {
+    /// Switches to the alternate screen on the given stream and tracks it.
+    ///
+    /// The nesting depth is incremented and the [`restore_all`](fn.restore_all.html)
+    /// function leaves the alternate screen, but only if the sequence was written. Fails
+    /// with the `InvalidInput` error for the standard input.
+    pub fn execute_tracked(&self, stream: StdStream) -> Result<()> {
+        enter_tracked(alternate::Flavor::Cleared, stream)
+    }
+}
+
 impl Command for EnterAlternateScreen {
-    type AnsiType = &'static str;
+    type AnsiType = Cow<'static, str>;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        enter_sequence(alternate::Flavor::Cleared)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        enter_tracked(alternate::Flavor::Cleared, StdStream::Stdout)
+    }
+}
+
+/// A command to switch to the alternate screen which neither saves the cursor nor
+/// clears the alternate screen (`?47h`).
+///
+/// See the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command for more
+/// information.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct EnterPreservedAlternateScreen;
+
+impl EnterPreservedAlternateScreen {
+    /// Switches to the alternate screen on the given stream and tracks it.
+    ///
+    /// See the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command for more
+    /// information.
+    pub fn execute_tracked(&self, stream: StdStream) -> Result<()> {
+        enter_tracked(alternate::Flavor::Preserved, stream)
+    }
+}
+
+impl Command for EnterPreservedAlternateScreen {
+    type AnsiType = Cow<'static, str>;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        enter_sequence(alternate::Flavor::Preserved)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        enter_tracked(alternate::Flavor::Preserved, StdStream::Stdout)
+    }
+}
+
+/// A command to save the cursor (`?1048h`), switch to the alternate screen (`?47h`),
+/// and clear it.
+///
+/// It behaves like the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command
+/// on terminals which mishandle `?1049h`.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct EnterComposedAlternateScreen;
+
+impl EnterComposedAlternateScreen {
+    /// Switches to the alternate screen on the given stream and tracks it.
+    ///
+    /// See the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command for more
+    /// information.
+    pub fn execute_tracked(&self, stream: StdStream) -> Result<()> {
+        enter_tracked(alternate::Flavor::Composed, stream)
+    }
+}
+
+impl Command for EnterComposedAlternateScreen {
+    type AnsiType = Cow<'static, str>;
 
     fn ansi_code(&self) -> Self::AnsiType {
-        alternate::ansi::ENTER_ALTERNATE_SCREEN_CSI_SEQUENCE
+        enter_sequence(alternate::Flavor::Composed)
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        let alternate = alternate::alternate_screen();
-        alternate.enter()
+        enter_tracked(alternate::Flavor::Composed, StdStream::Stdout)
     }
 }
 
 /// A command to switch back to the main screen.
 ///
+/// The scroll region, the origin mode, the line wrap, the mouse capture, the bracketed
+/// paste, the focus reporting, and the application keypad are reset before the main
+/// screen is shown. A broken
+/// scroll region (or any other mode) set on the alternate screen doesn't leak to the main
+/// screen.
+///
 /// # Notes
 ///
 /// Commands must be executed/queued for execution otherwise they do nothing.
 ///
+/// The command only writes the sequence. The flavor of the tracked alternate screen is
+/// left, the cleared one (`?1049l`) otherwise. Use the
+/// [`execute_tracked`](#method.execute_tracked) method to leave the tracked alternate
+/// screen, see the [`AlternateScreen`](struct.AlternateScreen.html) documentation.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -204,16 +997,178 @@ impl Command for EnterAlternateScreen {
 /// ```
 pub struct LeaveAlternateScreen;
 
+impl LeaveAlternateScreen {
+    /// Switches back to the main screen if it's the outermost tracked alternate screen.
+    ///
+    /// The sequence is written to the stream the alternate screen was entered on. The
+    /// nesting depth is decremented otherwise.
+    pub fn execute_tracked(&self) -> Result<()> {
+        leave_tracked(false)
+    }
+}
+
 impl Command for LeaveAlternateScreen {
-    type AnsiType = &'static str;
+    type AnsiType = Cow<'static, str>;
 
     fn ansi_code(&self) -> Self::AnsiType {
-        alternate::ansi::LEAVE_ALTERNATE_SCREEN_CSI_SEQUENCE
+        leave_sequence(false)
     }
 
     #[cfg(windows)]
     fn execute_winapi(&self) -> Result<()> {
-        let alternate = alternate::alternate_screen();
+        leave_tracked(false)
+    }
+}
+
+/// A command to clear the alternate screen and switch back to the main screen.
+///
+/// Some terminals briefly flash the stale content when the alternate screen is entered
+/// again later. The legacy Windows console (no ANSI support) always clears the
+/// alternate screen. See the [`LeaveAlternateScreen`](struct.LeaveAlternateScreen.html)
+/// command for more information.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct ClearAndLeaveAlternateScreen;
+
+impl ClearAndLeaveAlternateScreen {
+    /// Clears the alternate screen and switches back to the main screen if it's the
+    /// outermost tracked alternate screen.
+    ///
+    /// See the [`LeaveAlternateScreen`](struct.LeaveAlternateScreen.html) command for more
+    /// information.
+    pub fn execute_tracked(&self) -> Result<()> {
+        leave_tracked(true)
+    }
+}
+
+impl Command for ClearAndLeaveAlternateScreen {
+    type AnsiType = Cow<'static, str>;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        leave_sequence(true)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        leave_tracked(true)
+    }
+}
+
+// Returns the sequence to enter the alternate screen, empty if it's disabled
+fn enter_sequence(flavor: alternate::Flavor) -> Cow<'static, str> {
+    if alternate::is_disabled() {
+        return Cow::Borrowed("");
+    }
+
+    alternate::ansi::enter_sequence(alternate::resolve_flavor(flavor))
+}
+
+// Returns the sequence to leave the alternate screen, empty if it's disabled (unless the
+// alternate screen was entered before it was disabled)
+fn leave_sequence(clear: bool) -> Cow<'static, str> {
+    if alternate::is_disabled() && !alternate::is_active() {
+        return Cow::Borrowed("");
+    }
+
+    alternate::ansi::clear_reset_and_leave_sequence(clear)
+}
+
+// Enters the alternate screen on the given stream, the state is updated by the alternate
+// screen after the sequence is written
+fn enter_tracked(flavor: alternate::Flavor, stream: StdStream) -> Result<()> {
+    let output = match stream {
+        StdStream::Stdout => alternate::Output::Stdout,
+        StdStream::Stderr => alternate::Output::Stderr,
+        StdStream::Stdin => {
+            return Err(ErrorKind::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The alternate screen can't be entered on the standard input",
+            )));
+        }
+    };
+
+    alternate::enter_nested(|| alternate::alternate_screen(output, flavor).enter())
+}
+
+// Leaves the tracked alternate screen on the stream it was entered on
+fn leave_tracked(clear: bool) -> Result<()> {
+    alternate::leave_nested(|| {
+        let alternate =
+            alternate::alternate_screen(alternate::active_output(), alternate::flavor());
+
+        if clear && alternate::is_active() && !alternate::is_disabled() {
+            alternate.write(alternate::ansi::CLEAR_SCREEN_CSI_SEQUENCE)?;
+        }
+
         alternate.leave()
+    })
+}
+
+/// A command to save the cursor before the alternate screen is entered (`?1048h`).
+///
+/// Use it with the preserved alternate screen (`?47h`) on terminals which mishandle
+/// `?1049h`, see the [`EnterAlternateScreen`](struct.EnterAlternateScreen.html) command.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support), the cursor of the main
+/// screen buffer is always restored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{
+///     execute, Result, EnterPreservedAlternateScreen, LeaveAlternateScreen,
+///     RestoreCursorFromAlternate, SaveCursorForAlternate,
+/// };
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SaveCursorForAlternate, EnterPreservedAlternateScreen)?;
+///
+///     // Do anything on the alternate screen
+///
+///     execute!(stdout(), LeaveAlternateScreen, RestoreCursorFromAlternate)
+/// }
+/// ```
+pub struct SaveCursorForAlternate;
+
+impl Command for SaveCursorForAlternate {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        alternate::ansi::SAVE_CURSOR_CSI_SEQUENCE
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to restore the cursor saved by the
+/// [`SaveCursorForAlternate`](struct.SaveCursorForAlternate.html) command (`?1048l`).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+pub struct RestoreCursorFromAlternate;
+
+impl Command for RestoreCursorFromAlternate {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        alternate::ansi::RESTORE_CURSOR_CSI_SEQUENCE
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
     }
 }
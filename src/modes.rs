@@ -0,0 +1,603 @@
+#[cfg(windows)]
+use crossterm_utils::Result;
+use crossterm_utils::{csi, Command};
+
+use crate::dumb;
+
+/// A command to enable the line wrap (`DECAWM`).
+///
+/// The text written past the last column continues on the next line (the default).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) sets the `ENABLE_WRAP_AT_EOL_OUTPUT`
+/// console output mode.
+pub struct EnableLineWrap;
+
+impl Command for EnableLineWrap {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?7h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::set_wrap_at_eol(true)
+    }
+}
+
+/// A command to disable the line wrap (`DECAWM`).
+///
+/// The text written past the last column overwrites the last column. Full-width
+/// renderers can write the bottom-right cell without scrolling the screen.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) clears the `ENABLE_WRAP_AT_EOL_OUTPUT`
+/// console output mode.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableLineWrap, EnableLineWrap};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), DisableLineWrap)?;
+///
+///     // Render the full-width UI
+///
+///     execute!(stdout(), EnableLineWrap)
+/// }
+/// ```
+pub struct DisableLineWrap;
+
+impl Command for DisableLineWrap {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?7l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::set_wrap_at_eol(false)
+    }
+}
+
+/// A command to enable the reverse video screen mode (`DECSCNM`).
+///
+/// The foreground and background colors of the whole screen are swapped. It's useful
+/// for the visual bell (flash the screen) or the accessibility themes.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) swaps the foreground and background
+/// attributes of the visible window and of the default text attributes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossterm_screen::{execute, Result, DisableReverseScreen, EnableReverseScreen};
+///
+/// fn main() -> Result<()> {
+///     // Visual bell
+///     execute!(stdout(), EnableReverseScreen)?;
+///     thread::sleep(Duration::from_millis(100));
+///     execute!(stdout(), DisableReverseScreen)
+/// }
+/// ```
+pub struct EnableReverseScreen;
+
+impl Command for EnableReverseScreen {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?5h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::set_reverse_screen(true)
+    }
+}
+
+/// A command to disable the reverse video screen mode (`DECSCNM`).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct DisableReverseScreen;
+
+impl Command for DisableReverseScreen {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?5l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::set_reverse_screen(false)
+    }
+}
+
+/// A command to enable the origin mode (`DECOM`).
+///
+/// The cursor addressing is relative to the scroll region (see the
+/// [`SetScrollRegion`](struct.SetScrollRegion.html) command) and the cursor can't leave
+/// it. It allows to render a split pane as if it was the whole screen. The cursor is
+/// moved to the origin of the scroll region.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableOriginMode, EnableOriginMode, SetScrollRegion};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SetScrollRegion(10, 20), EnableOriginMode)?;
+///
+///     // Render the pane, the row 0 is the row 10 of the screen
+///
+///     execute!(stdout(), DisableOriginMode)
+/// }
+/// ```
+pub struct EnableOriginMode;
+
+impl Command for EnableOriginMode {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?6h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to disable the origin mode (`DECOM`).
+///
+/// The cursor addressing is relative to the whole screen (the default). The cursor is
+/// moved to the origin of the screen.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+pub struct DisableOriginMode;
+
+impl Command for DisableOriginMode {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?6l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to enable the application keypad mode (`DECKPAM`).
+///
+/// The numeric keypad sends the application sequences (like `ESC O p` for `0`) instead
+/// of the digits. The numeric keypad mode is set again when the alternate screen is
+/// left.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableApplicationKeypad, EnableApplicationKeypad};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), EnableApplicationKeypad)?;
+///
+///     // Read the keypad keys
+///
+///     execute!(stdout(), DisableApplicationKeypad)
+/// }
+/// ```
+pub struct EnableApplicationKeypad;
+
+impl Command for EnableApplicationKeypad {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb("\x1B=")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to set the numeric keypad mode (`DECKPNM`).
+///
+/// The numeric keypad sends the digits (the default).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+pub struct DisableApplicationKeypad;
+
+impl Command for DisableApplicationKeypad {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb("\x1B>")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to enable the insert mode (`IRM`).
+///
+/// The characters written at the cursor shift the rest of the line to the right instead
+/// of replacing it. Line editors can insert a character without repainting the rest of
+/// the line.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableInsertMode, EnableInsertMode};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), EnableInsertMode)?;
+///
+///     write!(stdout(), "x")?;
+///
+///     execute!(stdout(), DisableInsertMode)
+/// }
+/// ```
+pub struct EnableInsertMode;
+
+impl Command for EnableInsertMode {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("4h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to disable the insert mode (`IRM`).
+///
+/// The characters written at the cursor replace the existing ones (the default).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+pub struct DisableInsertMode;
+
+impl Command for DisableInsertMode {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("4l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to switch the terminal to 132 columns (`DECCOLM`).
+///
+/// The column switching is allowed (`?40h`) first, some terminals ignore `DECCOLM`
+/// otherwise. The screen is cleared and the scroll region is reset by the terminal.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop if the terminal doesn't support the column switching, see the
+/// [`is_supported`](#method.is_supported) method.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, SetColumns132, SetColumns80};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SetColumns132)?;
+///
+///     // Render the wide report
+///
+///     execute!(stdout(), SetColumns80)
+/// }
+/// ```
+pub struct SetColumns132;
+
+impl SetColumns132 {
+    /// Checks whether the terminal supports the column switching.
+    ///
+    /// On UNIX, `$TERM` must be a VT100 compatible terminal (`vt1xx`+) or `xterm`. The
+    /// Windows console supports it if it supports the virtual terminal sequences.
+    pub fn is_supported() -> bool {
+        column_mode_supported()
+    }
+}
+
+impl Command for SetColumns132 {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if column_mode_supported() {
+            csi!("?40h", "\x1B[?3h")
+        } else {
+            ""
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to switch the terminal to 80 columns (`DECCOLM`).
+///
+/// The screen is cleared and the scroll region is reset by the terminal.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop if the terminal doesn't support the column switching, see the
+/// [`SetColumns132::is_supported`](struct.SetColumns132.html#method.is_supported) method.
+pub struct SetColumns80;
+
+impl Command for SetColumns80 {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if column_mode_supported() {
+            csi!("?40h", "\x1B[?3l")
+        } else {
+            ""
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to save the state of the given private modes (`XTSAVE`).
+///
+/// Save the modes (like the line wrap `7`, mouse capture `1000` or bracketed paste
+/// `2004`) before changing them and restore them precisely on exit with the
+/// [`RestoreModes`](struct.RestoreModes.html) command.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the support ignore it. It's a noop if no mode is given or on the
+/// legacy Windows console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableLineWrap, RestoreModes, SaveModes};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), SaveModes(&[7, 2004]), DisableLineWrap)?;
+///
+///     // Render the full-width UI
+///
+///     execute!(stdout(), RestoreModes(&[7, 2004]))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveModes<'a>(pub &'a [u16]);
+
+impl<'a> Command for SaveModes<'a> {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(private_modes_sequence(self.0, 's'))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to restore the state of the given private modes (`XTRESTORE`).
+///
+/// The modes must be saved with the [`SaveModes`](struct.SaveModes.html) command first.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the support ignore it. It's a noop if no mode is given or on the
+/// legacy Windows console (no ANSI support).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreModes<'a>(pub &'a [u16]);
+
+impl<'a> Command for RestoreModes<'a> {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(private_modes_sequence(self.0, 'r'))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn private_modes_sequence(modes: &[u16], action: char) -> String {
+    if modes.is_empty() {
+        return String::new();
+    }
+
+    let modes = modes
+        .iter()
+        .map(|mode| mode.to_string())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    format!(csi!("?{}{}"), modes, action)
+}
+
+// The legacy console never gets here (`execute_winapi` is used)
+#[cfg(windows)]
+fn column_mode_supported() -> bool {
+    crossterm_utils::supports_ansi()
+}
+
+#[cfg(unix)]
+fn column_mode_supported() -> bool {
+    // VT100 (and newer) & xterm compatible terminals
+    const PREFIXES: [&str; 6] = ["xterm", "vt1", "vt2", "vt3", "vt4", "vt5"];
+
+    match std::env::var("TERM") {
+        Ok(term) => PREFIXES.iter().any(|prefix| term.starts_with(prefix)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::mem;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use crossterm_utils::Result;
+    use crossterm_winapi::{ConsoleMode, Handle};
+    use winapi::shared::minwindef::WORD;
+    use winapi::um::wincon::{
+        GetConsoleScreenBufferInfo, ReadConsoleOutputAttribute, SetConsoleTextAttribute,
+        WriteConsoleOutputAttribute, CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_WRAP_AT_EOL_OUTPUT,
+    };
+    use winapi::um::winnt::HANDLE;
+
+    use crate::alternate::windows::alternate_screen_buffer;
+    use crate::sys::winapi::result;
+
+    // `true` if the attributes are swapped by the `EnableReverseScreen` command
+    static REVERSE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+    // Swaps the foreground & background attributes of the visible window and of the
+    // default text attributes (once per enable/disable)
+    pub(super) fn set_reverse_screen(enabled: bool) -> Result<()> {
+        if REVERSE_SCREEN.load(Ordering::SeqCst) == enabled {
+            return Ok(());
+        }
+
+        let handle = screen_buffer()?;
+
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+
+        let origin = COORD {
+            X: 0,
+            Y: info.srWindow.Top,
+        };
+        let length = info.dwSize.X as u32 * (info.srWindow.Bottom - info.srWindow.Top + 1) as u32;
+
+        let mut attributes: Vec<WORD> = vec![0; length as usize];
+        let mut count = 0;
+        result(unsafe {
+            ReadConsoleOutputAttribute(handle, attributes.as_mut_ptr(), length, origin, &mut count)
+        })?;
+
+        for attribute in attributes.iter_mut() {
+            *attribute = swap_colors(*attribute);
+        }
+
+        let mut written = 0;
+        result(unsafe {
+            WriteConsoleOutputAttribute(handle, attributes.as_ptr(), count, origin, &mut written)
+        })?;
+        result(unsafe { SetConsoleTextAttribute(handle, swap_colors(info.wAttributes)) })?;
+
+        // Keep it last - flip the flag only if we were able to swap the attributes
+        REVERSE_SCREEN.store(enabled, Ordering::SeqCst);
+        Ok(())
+    }
+
+    // Swaps the foreground (low nibble) & background (high nibble) colors
+    fn swap_colors(attribute: WORD) -> WORD {
+        let foreground = attribute & 0x0F;
+        let background = (attribute & 0xF0) >> 4;
+        (attribute & !0xFF) | (foreground << 4) | background
+    }
+
+    // Sets or clears the `ENABLE_WRAP_AT_EOL_OUTPUT` mode of the active screen buffer
+    pub(super) fn set_wrap_at_eol(enabled: bool) -> Result<()> {
+        let console_mode = ConsoleMode::from(screen_buffer()?);
+        let mode = console_mode.mode()?;
+
+        if enabled {
+            console_mode.set_mode(mode | ENABLE_WRAP_AT_EOL_OUTPUT)?;
+        } else {
+            console_mode.set_mode(mode & !ENABLE_WRAP_AT_EOL_OUTPUT)?;
+        }
+
+        Ok(())
+    }
+
+    // Returns the active screen buffer (the alternate one if it's active)
+    fn screen_buffer() -> Result<HANDLE> {
+        match alternate_screen_buffer() {
+            Some(handle) => Ok(handle),
+            None => Ok(Handle::output_handle()?),
+        }
+    }
+}
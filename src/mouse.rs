@@ -0,0 +1,314 @@
+use std::io::Write;
+
+use crossterm_utils::{csi, write_cout, Command, ExecutableCommand, Result};
+
+use crate::alternate::ansi::{
+    DISABLE_MOUSE_CAPTURE_CSI_SEQUENCE, ENABLE_MOUSE_ALL_MOTION_CSI_SEQUENCE,
+    ENABLE_MOUSE_CLICKS_CSI_SEQUENCE, ENABLE_MOUSE_DRAG_CSI_SEQUENCE,
+};
+use crate::{dumb, restore};
+
+const MOUSE_CAPTURE_STATE_CHANGE: &str = "mouse capture";
+
+/// Which mouse events are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseTracking {
+    /// The button presses & releases and the wheel (`?1000`).
+    Clicks,
+    /// The clicks and the motion while a button is pressed (`?1002`).
+    Drag,
+    /// The clicks and all the motion events (`?1003`).
+    AllMotion,
+}
+
+/// A command to enable the mouse capture.
+///
+/// The mouse events are reported in the SGR encoding (`?1006`). The clicks and the drag
+/// (`?1002`) are reported, use the [`EnableMouseTracking`](struct.EnableMouseTracking.html)
+/// command to pick the granularity. The mouse capture enabled via the
+/// [`execute_tracked`](#method.execute_tracked) method is disabled by the
+/// [`restore_all`](fn.restore_all.html) function as well.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) sets the `ENABLE_MOUSE_INPUT` console
+/// input mode (and clears the `ENABLE_QUICK_EDIT_MODE` which swallows the mouse events),
+/// the granularity is ignored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, DisableMouseCapture, EnableMouseCapture};
+///
+/// fn main() -> Result<()> {
+///     EnableMouseCapture.execute_tracked(&mut stdout())?;
+///
+///     // Read the mouse events
+///
+///     DisableMouseCapture.execute_tracked(&mut stdout())
+/// }
+/// ```
+pub struct EnableMouseCapture;
+
+impl EnableMouseCapture {
+    /// Enables the mouse capture and tracks it for the
+    /// [`restore_all`](fn.restore_all.html) function.
+    ///
+    /// The mouse capture is tracked only if it was enabled.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        EnableMouseTracking(MouseTracking::Drag).execute_tracked(writer)
+    }
+}
+
+impl Command for EnableMouseCapture {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        EnableMouseTracking(MouseTracking::Drag).ansi_code()
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        EnableMouseTracking(MouseTracking::Drag).execute_winapi()
+    }
+}
+
+/// A command to enable the mouse capture with the given granularity.
+///
+/// It's the [`EnableMouseCapture`](struct.EnableMouseCapture.html) command reporting the
+/// given mouse events.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{
+///     execute, Result, DisableMouseCapture, EnableMouseTracking, MouseTracking,
+/// };
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), EnableMouseTracking(MouseTracking::AllMotion))?;
+///
+///     // Read the mouse events
+///
+///     execute!(stdout(), DisableMouseCapture)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableMouseTracking(pub MouseTracking);
+
+impl EnableMouseTracking {
+    /// Enables the mouse capture and tracks it for the
+    /// [`restore_all`](fn.restore_all.html) function.
+    ///
+    /// The mouse capture is tracked only if it was enabled.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(*self)?;
+
+        // Keep it last - track the mouse capture only if we were able to enable it
+        if !dumb::suppressed() {
+            restore::register(MOUSE_CAPTURE_STATE_CHANGE, disable);
+        }
+
+        Ok(())
+    }
+}
+
+impl Command for EnableMouseTracking {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if dumb::suppressed() {
+            return "";
+        }
+
+        match self.0 {
+            MouseTracking::Clicks => ENABLE_MOUSE_CLICKS_CSI_SEQUENCE,
+            MouseTracking::Drag => ENABLE_MOUSE_DRAG_CSI_SEQUENCE,
+            MouseTracking::AllMotion => ENABLE_MOUSE_ALL_MOTION_CSI_SEQUENCE,
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::enable()
+    }
+}
+
+/// A command to disable the mouse capture.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) restores the console input mode flags
+/// changed by the [`EnableMouseCapture`](struct.EnableMouseCapture.html) command.
+pub struct DisableMouseCapture;
+
+impl DisableMouseCapture {
+    /// Disables the mouse capture enabled by the
+    /// [`EnableMouseTracking::execute_tracked`](struct.EnableMouseTracking.html#method.execute_tracked)
+    /// method.
+    ///
+    /// The mouse capture is forgotten only if it was disabled.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(DisableMouseCapture)?;
+
+        // Keep it last - forget the mouse capture only if we were able to disable it
+        restore::unregister(MOUSE_CAPTURE_STATE_CHANGE);
+        Ok(())
+    }
+}
+
+impl Command for DisableMouseCapture {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(DISABLE_MOUSE_CAPTURE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::disable()
+    }
+}
+
+/// A command to enable the alternate scroll mode (`?1007h`).
+///
+/// The mouse wheel events are translated to the arrow up & down keys while the
+/// alternate screen is active and the mouse is not captured. Pagers and editors can be
+/// scrolled with the mouse wheel without the mouse capture.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the support ignore it. It's a noop on the legacy Windows console
+/// (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{
+///     execute, Result, DisableAlternateScroll, EnableAlternateScroll, EnterAlternateScreen,
+///     LeaveAlternateScreen,
+/// };
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), EnterAlternateScreen, EnableAlternateScroll)?;
+///
+///     // Read the arrow keys
+///
+///     execute!(stdout(), DisableAlternateScroll, LeaveAlternateScreen)
+/// }
+/// ```
+pub struct EnableAlternateScroll;
+
+impl Command for EnableAlternateScroll {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?1007h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to disable the alternate scroll mode (`?1007l`).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on the legacy Windows console (no ANSI support).
+pub struct DisableAlternateScroll;
+
+impl Command for DisableAlternateScroll {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?1007l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Disables the mouse capture (the restore function)
+fn disable() -> Result<()> {
+    restore::unregister(MOUSE_CAPTURE_STATE_CHANGE);
+
+    #[cfg(windows)]
+    {
+        if !crossterm_utils::supports_ansi() {
+            return legacy::disable();
+        }
+    }
+
+    write_cout!(DISABLE_MOUSE_CAPTURE_CSI_SEQUENCE)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::sync::Mutex;
+
+    use crossterm_utils::Result;
+    use lazy_static::lazy_static;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::wincon::{ENABLE_EXTENDED_FLAGS, ENABLE_MOUSE_INPUT, ENABLE_QUICK_EDIT_MODE};
+
+    use crate::sys;
+
+    // The console input mode flags changed by the mouse capture
+    const MOUSE_FLAGS: DWORD = ENABLE_MOUSE_INPUT | ENABLE_QUICK_EDIT_MODE | ENABLE_EXTENDED_FLAGS;
+
+    lazy_static! {
+        // The mouse flags prior the mouse capture
+        static ref ORIGINAL_MOUSE_FLAGS: Mutex<Option<DWORD>> = Mutex::new(None);
+    }
+
+    // The console input mode is changed via the raw mode command, the change is serialized
+    // with the raw mode and survives the raw mode disable
+    pub(super) fn enable() -> Result<()> {
+        let mode = sys::RawModeCommand::new()?.update_mode(|mode| {
+            (mode | ENABLE_MOUSE_INPUT | ENABLE_EXTENDED_FLAGS) & !ENABLE_QUICK_EDIT_MODE
+        })?;
+
+        // Keep the very first original flags if it's enabled several times
+        ORIGINAL_MOUSE_FLAGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert(mode & MOUSE_FLAGS);
+
+        Ok(())
+    }
+
+    pub(super) fn disable() -> Result<()> {
+        let original = ORIGINAL_MOUSE_FLAGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+
+        if let Some(original) = original {
+            // `ENABLE_EXTENDED_FLAGS` must be set to change the Quick Edit mode
+            sys::RawModeCommand::new()?
+                .update_mode(|mode| (mode & !MOUSE_FLAGS) | original | ENABLE_EXTENDED_FLAGS)?;
+        }
+
+        Ok(())
+    }
+}
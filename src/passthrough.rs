@@ -0,0 +1,92 @@
+use std::env;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// The forced passthrough mode, `AUTO` if it's detected from the environment
+static FORCED_PASSTHROUGH: AtomicU8 = AtomicU8::new(AUTO);
+
+const AUTO: u8 = 0;
+const DISABLED: u8 = 1;
+const TMUX: u8 = 2;
+const SCREEN: u8 = 3;
+
+/// The DCS passthrough envelope of a terminal multiplexer.
+///
+/// Terminal multiplexers swallow the sequences they don't know (like the synchronized
+/// update or the title stack) instead of forwarding them to the outer terminal. The
+/// sequences wrapped in the passthrough envelope are forwarded. The synchronized update
+/// and the title stack commands use the envelope. The other sequences (like the
+/// alternate screen) are handled by the multiplexer itself and they're not wrapped.
+///
+/// The mode is detected from the environment (`$TMUX` & `$TERM`) unless it's forced
+/// via the [`force`](#method.force) method. tmux 3.3+ forwards the wrapped sequences
+/// only if the `allow-passthrough` option is enabled.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::Passthrough;
+///
+/// // Never wrap the sequences
+/// Passthrough::force(Some(Passthrough::Disabled));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Passthrough {
+    /// The sequences are written as they are.
+    Disabled,
+    /// The sequences are wrapped in the tmux envelope (`ESC P tmux; ... ESC \`).
+    Tmux,
+    /// The sequences are wrapped in the GNU screen envelope (`ESC P ... ESC \`).
+    Screen,
+}
+
+impl Passthrough {
+    /// Detects the passthrough mode from the environment.
+    ///
+    /// `Tmux` if `$TMUX` is set, `Screen` if `$TERM` starts with `screen` (and it's not
+    /// tmux), `Disabled` otherwise.
+    pub fn detect() -> Passthrough {
+        if env::var_os("TMUX").is_some_and(|tmux| !tmux.is_empty()) {
+            return Passthrough::Tmux;
+        }
+
+        match env::var("TERM") {
+            Ok(ref term) if term.starts_with("screen") => Passthrough::Screen,
+            _ => Passthrough::Disabled,
+        }
+    }
+
+    /// Forces the passthrough mode, `None` detects it from the environment again.
+    pub fn force(passthrough: Option<Passthrough>) {
+        let value = match passthrough {
+            None => AUTO,
+            Some(Passthrough::Disabled) => DISABLED,
+            Some(Passthrough::Tmux) => TMUX,
+            Some(Passthrough::Screen) => SCREEN,
+        };
+
+        FORCED_PASSTHROUGH.store(value, Ordering::SeqCst);
+    }
+
+    /// Returns the passthrough mode in use (forced or detected).
+    pub fn current() -> Passthrough {
+        match FORCED_PASSTHROUGH.load(Ordering::SeqCst) {
+            DISABLED => Passthrough::Disabled,
+            TMUX => Passthrough::Tmux,
+            SCREEN => Passthrough::Screen,
+            _ => Passthrough::detect(),
+        }
+    }
+
+    /// Wraps the sequence in the passthrough envelope.
+    ///
+    /// The escape characters of the sequence are doubled for tmux.
+    pub fn wrap(self, sequence: &str) -> String {
+        match self {
+            Passthrough::Disabled => sequence.to_string(),
+            Passthrough::Tmux => {
+                format!("\x1BPtmux;{}\x1B\\", sequence.replace('\x1B', "\x1B\x1B"))
+            }
+            Passthrough::Screen => format!("\x1BP{}\x1B\\", sequence),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Double-buffered frame presenter.
+
+use std::io::Write;
+
+use crossterm_utils::{Command, Result};
+
+use crate::{BeginSynchronizedUpdate, EndSynchronizedUpdate, VirtualScreen};
+
+/// A double-buffered presenter of the frames.
+///
+/// Draw the frame into the virtual screen returned by the [`frame`](#method.frame)
+/// method and show it with the [`present`](#method.present) method. Only the changed
+/// cells are written (see [`VirtualScreen`](struct.VirtualScreen.html)) and they're
+/// wrapped in the synchronized update (see
+/// [`BeginSynchronizedUpdate`](struct.BeginSynchronizedUpdate.html)), the terminal shows
+/// the whole frame at once. There's no tearing & flickering out of the box.
+///
+/// # Notes
+///
+/// The frame is written with a single `WriteConsoleOutput` call to the active screen
+/// buffer (the alternate one if it's active) on the legacy Windows console (no ANSI
+/// support), the writer is not used there.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stdout;
+/// use crossterm_screen::{size, AlternateScreen, Presenter};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let _alternate = AlternateScreen::to_alternate(true)?;
+///
+///     let (columns, rows) = size()?;
+///     let mut presenter = Presenter::new(stdout(), columns, rows);
+///
+///     for frame in 0..100 {
+///         presenter.frame().print(frame % columns, 0, "Hello");
+///         presenter.present()?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub struct Presenter<W: Write> {
+    writer: W,
+    screen: VirtualScreen,
+}
+
+impl<W: Write> Presenter<W> {
+    /// Creates a presenter of the frames of the given size writing to the writer.
+    pub fn new(writer: W, columns: u16, rows: u16) -> Presenter<W> {
+        Presenter {
+            writer,
+            screen: VirtualScreen::new(columns, rows),
+        }
+    }
+
+    /// Begins a new frame, returns the cleared virtual screen to draw it into.
+    pub fn frame(&mut self) -> &mut VirtualScreen {
+        self.screen.clear();
+        &mut self.screen
+    }
+
+    /// Shows the frame drawn since the last [`frame`](#method.frame) call.
+    ///
+    /// Nothing is written if the frame didn't change. The whole frame is written again
+    /// with the next call if it fails.
+    pub fn present(&mut self) -> Result<()> {
+        #[cfg(windows)]
+        {
+            if !crate::alternate::prefers_ansi() {
+                return legacy::present(&mut self.screen);
+            }
+        }
+
+        let mut changes = Vec::new();
+        self.screen.flush(&mut changes)?;
+
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut output = BeginSynchronizedUpdate.ansi_code().into_bytes();
+        output.extend(changes);
+        output.extend(EndSynchronizedUpdate.ansi_code().into_bytes());
+
+        let result = self
+            .writer
+            .write_all(&output)
+            .and_then(|_| self.writer.flush());
+
+        if result.is_err() {
+            // The terminal shows a partial frame, write the whole one next time
+            self.screen.invalidate();
+        }
+
+        Ok(result?)
+    }
+
+    /// Resizes the frames, the next frame is written as a whole.
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        self.screen.resize(columns, rows);
+    }
+
+    /// Forces the next frame to be written as a whole.
+    pub fn invalidate(&mut self) {
+        self.screen.invalidate();
+    }
+
+    /// Sets whether the presented frames are returned by the
+    /// [`AlternateScreen::capture`](struct.AlternateScreen.html#method.capture) method
+    /// (disabled by default), see the
+    /// [`VirtualScreen::capture_frames`](struct.VirtualScreen.html#method.capture_frames)
+    /// method.
+    pub fn capture_frames(&mut self, capture: bool) {
+        self.screen.capture_frames(capture);
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::mem;
+
+    use crossterm_utils::Result;
+    use winapi::um::wincon::{
+        GetConsoleScreenBufferInfo, WriteConsoleOutputW, CHAR_INFO, COMMON_LVB_LEADING_BYTE,
+        COMMON_LVB_TRAILING_BYTE, CONSOLE_SCREEN_BUFFER_INFO, COORD, SMALL_RECT,
+    };
+
+    use crate::alternate::windows::{alternate_screen_buffer, main_screen_buffer};
+    use crate::buffer::CONTINUATION;
+    use crate::sys::winapi::result;
+    use crate::VirtualScreen;
+
+    // Writes the whole frame to the visible window of the active screen buffer
+    pub(super) fn present(screen: &mut VirtualScreen) -> Result<()> {
+        let handle = match alternate_screen_buffer() {
+            Some(handle) => handle,
+            None => main_screen_buffer()?,
+        };
+
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+
+        let (columns, rows) = screen.size();
+
+        let frame = screen.cells();
+        let cells: Vec<CHAR_INFO> = frame
+            .iter()
+            .enumerate()
+            .map(|(index, character)| {
+                // The wide characters are written to both of their cells
+                let (character, flags) = if *character == CONTINUATION {
+                    (&frame[index - 1], COMMON_LVB_TRAILING_BYTE)
+                } else if frame.get(index + 1) == Some(&CONTINUATION) {
+                    (character, COMMON_LVB_LEADING_BYTE)
+                } else {
+                    (character, 0)
+                };
+
+                let mut cell: CHAR_INFO = unsafe { mem::zeroed() };
+                // The console cells hold a single UTF-16 code unit
+                let character = if (*character as u32) <= 0xFFFF {
+                    *character as u16
+                } else {
+                    '?' as u16
+                };
+                unsafe { *cell.Char.UnicodeChar_mut() = character };
+                cell.Attributes = info.wAttributes | flags;
+                cell
+            })
+            .collect();
+
+        let mut region = SMALL_RECT {
+            Left: info.srWindow.Left,
+            Top: info.srWindow.Top,
+            Right: info.srWindow.Left + columns as i16 - 1,
+            Bottom: info.srWindow.Top + rows as i16 - 1,
+        };
+
+        result(unsafe {
+            WriteConsoleOutputW(
+                handle,
+                cells.as_ptr(),
+                COORD {
+                    X: columns as i16,
+                    Y: rows as i16,
+                },
+                COORD { X: 0, Y: 0 },
+                &mut region,
+            )
+        })?;
+
+        // Keep it last - remember the frame only if we were able to write it
+        screen.set_flushed();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use crossterm_utils::{csi, Result};
+
+use crate::{termux, RawScreen};
+
+// How long to wait for the terminal response
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The state of a terminal mode reported by the terminal (`DECRPM`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModeState {
+    /// The mode is not recognized by the terminal (or the terminal didn't respond).
+    NotRecognized,
+    /// The mode is set.
+    Set,
+    /// The mode is reset.
+    Reset,
+    /// The mode is set and it can't be changed.
+    PermanentlySet,
+    /// The mode is reset and it can't be changed.
+    PermanentlyReset,
+}
+
+impl ModeState {
+    /// Checks whether the mode is (permanently) set.
+    pub fn is_set(self) -> bool {
+        self == ModeState::Set || self == ModeState::PermanentlySet
+    }
+
+    /// Checks whether the mode is recognized by the terminal.
+    pub fn is_recognized(self) -> bool {
+        self != ModeState::NotRecognized
+    }
+}
+
+/// Asks the terminal for the state of the private mode (`DECRQM`).
+///
+/// It's a best-effort query. The raw mode is enabled for the time of the query and the
+/// response is awaited for up to 100ms. `ModeState::NotRecognized` is returned if the
+/// terminal doesn't support the mode (or the query). Input typed at the same time can
+/// be consumed.
+///
+/// UNIX only.
+///
+/// # Arguments
+///
+/// * `mode` - the private mode number (like `1049` for the alternate screen, `2004` for
+///   the bracketed paste, or `2026` for the synchronized update)
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::query_mode;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     if query_mode(2026)?.is_recognized() {
+///         // Use the synchronized update
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn query_mode(mode: u16) -> Result<ModeState> {
+    let raw = RawScreen::into_raw_mode()?;
+
+    let request = format!(csi!("?{}$p"), mode);
+    let response = raw.command().query(&request, b'y', RESPONSE_TIMEOUT)?;
+
+    Ok(response
+        .map(|response| parse_mode_report(&response, mode))
+        .unwrap_or(ModeState::NotRecognized))
+}
+
+// Asks the terminal for the states of the private modes (`DECRQM`) at once
+//
+// The primary device attributes request (`DA1`) is appended, every terminal answers it
+// and the responses come in order - there's no need to wait for the modes the terminal
+// doesn't recognize. `None` is returned if the terminal doesn't respond at all.
+pub(crate) fn query_modes(modes: &[u16]) -> Result<Option<Vec<ModeState>>> {
+    let raw = RawScreen::into_raw_mode()?;
+
+    let mut request: String = modes
+        .iter()
+        .map(|mode| format!(csi!("?{}$p"), mode))
+        .collect();
+    request.push_str(csi!("c"));
+
+    let response = raw.command().query(&request, b'c', RESPONSE_TIMEOUT)?;
+
+    Ok(response.map(|response| {
+        modes
+            .iter()
+            .map(|mode| parse_mode_report(&response, *mode))
+            .collect()
+    }))
+}
+
+// Parses the DECRPM response (`CSI ? <mode> ; <value> $ y`) of the given mode
+fn parse_mode_report(response: &[u8], mode: u16) -> ModeState {
+    let response = String::from_utf8_lossy(response);
+    let prefix = format!("\x1B[?{};", mode);
+
+    let value = response
+        .find(&prefix)
+        .map(|start| &response[start + prefix.len()..])
+        .and_then(|value| value.split("$y").next());
+
+    match value {
+        Some("1") => ModeState::Set,
+        Some("2") => ModeState::Reset,
+        Some("3") => ModeState::PermanentlySet,
+        Some("4") => ModeState::PermanentlyReset,
+        _ => ModeState::NotRecognized,
+    }
+}
+
+/// Returns the terminal window position in pixels `(x, y)`.
+///
+/// The terminal is asked (`XTWINOPS 13`, see the
+/// [`XtWindowOp::ReportPosition`](enum.XtWindowOp.html#variant.ReportPosition)
+/// operation). The raw mode is enabled for the time of the query and the response is
+/// awaited for up to 100ms. `None` is returned if the terminal doesn't respond, and
+/// right away on Termux (no window).
+///
+/// UNIX only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::window_position;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     if let Some((x, y)) = window_position()? {
+///         println!("The window is at {}x{}", x, y);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn window_position() -> Result<Option<(u16, u16)>> {
+    if termux::is_termux() {
+        return Ok(None);
+    }
+
+    let raw = RawScreen::into_raw_mode()?;
+    let response = raw.command().query(csi!("13t"), b't', RESPONSE_TIMEOUT)?;
+
+    Ok(response.and_then(|response| parse_position_report(&response)))
+}
+
+// Parses the window position response (`CSI 3 ; <x> ; <y> t`), `(x, y)`
+fn parse_position_report(response: &[u8]) -> Option<(u16, u16)> {
+    let response = String::from_utf8_lossy(response);
+    let prefix = "\x1B[3;";
+
+    let start = response.find(prefix)? + prefix.len();
+    let mut values = response[start..].trim_end_matches('t').split(';');
+
+    let x = values.next()?.parse().ok()?;
+    let y = values.next()?.parse().ok()?;
+
+    Some((x, y))
+}
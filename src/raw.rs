@@ -1,8 +1,43 @@
-use std::io::{Stdout, Write};
+use std::collections::HashMap;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard};
 
 use crossterm_utils::Result;
+use lazy_static::lazy_static;
 
-use crate::sys;
+pub use self::echo::{disable_echo, enable_echo, NoEcho};
+pub use self::flow_control::NoFlowControl;
+pub use self::mode::TerminalMode;
+pub use self::options::RawModeOptions;
+pub use self::terminal::RawTerminal;
+#[cfg(windows)]
+use crate::ConsoleInputModeFlags;
+use crate::{restore, sys, AttributeChange, ScreenError, TerminalAttributes};
+
+mod echo;
+mod flow_control;
+mod mode;
+mod options;
+mod terminal;
+
+lazy_static! {
+    // `RawScreen` values which still hold the raw mode enabled (per terminal)
+    static ref RAW_MODE_GUARDS: Mutex<HashMap<sys::Tty, RawModeGuards>> = Mutex::new(HashMap::new());
+}
+
+struct RawModeGuards {
+    // Number of the `RawScreen` values
+    count: usize,
+    // The command which enabled the raw mode, the other values must use the same mode
+    command: sys::RawModeCommand,
+}
+
+const RAW_MODE_STATE_CHANGE: &str = "raw mode";
 
 /// A raw screen.
 ///
@@ -10,6 +45,22 @@ use crate::sys;
 /// Call the [`keep_raw_mode_on_drop`](struct.RawScreen.html#method.keep_raw_mode_on_drop)
 /// method to disable this behavior (keep the raw mode enabled).
 ///
+/// The `RawScreen` values are reference counted. If there're several of them, the
+/// raw mode is disabled when the last one is dropped. Use the
+/// [`disable_raw_mode`](struct.RawScreen.html#method.disable_raw_mode) method to
+/// disable the raw mode regardless of the other values.
+///
+/// All the values of a terminal share the same mode. Enabling a different mode (like the
+/// cbreak mode) while the raw mode is enabled fails with the
+/// [`ScreenError::ModeMismatch`](enum.ScreenError.html#variant.ModeMismatch) error.
+///
+/// # Thread safety
+///
+/// The raw mode can be enabled and disabled from several threads. All the terminal
+/// (unix) or console (windows) mode changes made by this crate are serialized, so
+/// concurrent changes do not race on the read-modify-write of the mode. Changes made
+/// by others (another crate calling `tcsetattr` directly) are not serialized.
+///
 /// # Examples
 ///
 /// Basic usage:
@@ -44,42 +95,406 @@ use crate::sys;
 /// }
 /// ```
 pub struct RawScreen {
+    command: sys::RawModeCommand,
     disable_raw_mode_on_drop: bool,
+    // Whether the job control handlers were installed for this value
+    #[cfg(unix)]
+    job_control: bool,
 }
 
 impl RawScreen {
     // TODO enable_raw_mode() to keep it synced with enable/disable?
     /// Enables raw mode.
     pub fn into_raw_mode() -> Result<RawScreen> {
+        RawScreen::enable(sys::RawModeCommand::new()?)
+    }
+
+    /// Enables raw mode with the given options.
+    ///
+    /// See the [`RawModeOptions`](struct.RawModeOptions.html) documentation for
+    /// more information.
+    pub fn into_raw_mode_with(options: RawModeOptions) -> Result<RawScreen> {
         #[cfg(unix)]
-        let mut command = sys::unix::RawModeCommand::new();
-        #[cfg(windows)]
-        let mut command = sys::winapi::RawModeCommand::new();
+        {
+            if options.job_control {
+                crate::job_control::install()?;
+            }
+        }
 
-        command.enable()?;
+        let result = sys::RawModeCommand::new().and_then(|command| {
+            RawScreen::enable_with(command.with_options(options), options.exclusive, |_| {})
+        });
 
-        Ok(RawScreen {
-            disable_raw_mode_on_drop: true,
+        #[cfg(unix)]
+        {
+            if options.job_control {
+                return match result {
+                    Ok(mut raw) => {
+                        raw.job_control = true;
+                        Ok(raw)
+                    }
+                    Err(error) => {
+                        crate::job_control::uninstall();
+                        Err(error)
+                    }
+                };
+            }
+        }
+
+        result
+    }
+
+    /// Enables raw mode, the given console input modes are cleared.
+    ///
+    /// It allows to pick exactly which console input modes are cleared, see the
+    /// [`ConsoleInputModeFlags`](struct.ConsoleInputModeFlags.html) documentation
+    /// for more information.
+    ///
+    /// Windows only.
+    #[cfg(windows)]
+    pub fn into_raw_mode_with_mask(mask: ConsoleInputModeFlags) -> Result<RawScreen> {
+        RawScreen::enable(sys::RawModeCommand::new()?.with_mask(mask))
+    }
+
+    /// Enables raw mode, the hook can adjust the terminal attributes before they're applied.
+    ///
+    /// The hook is called after the standard raw mode flags are set, but before the
+    /// attributes are applied (`tcsetattr`, `SetConsoleMode`). It's not called if the
+    /// raw mode is already enabled.
+    ///
+    /// # Examples
+    ///
+    /// Keep the `\n` to `\r\n` translation:
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let _raw = RawScreen::into_raw_mode_with_hook(|attributes| {
+    ///         #[cfg(unix)]
+    ///         {
+    ///             attributes.termios_mut().c_oflag |= libc::OPOST | libc::ONLCR;
+    ///         }
+    ///     })?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_raw_mode_with_hook<F>(hook: F) -> Result<RawScreen>
+    where
+        F: FnOnce(&mut TerminalAttributes),
+    {
+        RawScreen::enable_with(sys::RawModeCommand::new()?, false, |attributes| {
+            let mut terminal_attributes = TerminalAttributes::new(*attributes);
+            hook(&mut terminal_attributes);
+            *attributes = terminal_attributes.into_inner();
         })
     }
 
+    /// Enables raw mode, but keeps the signal processing.
+    ///
+    /// `Ctrl+C` still terminates the program, `Ctrl+Z` suspends it, etc. It's a
+    /// shortcut for the [`into_raw_mode_with`](struct.RawScreen.html#method.into_raw_mode_with)
+    /// method with the [`RawModeOptions::raw().signals(true)`](struct.RawModeOptions.html#method.signals)
+    /// options.
+    pub fn into_raw_mode_keep_signals() -> Result<RawScreen> {
+        RawScreen::into_raw_mode_with(RawModeOptions::raw().signals(true))
+    }
+
+    /// Enables cbreak mode.
+    ///
+    /// The cbreak mode (sometimes called half-raw mode) disables the line buffering
+    /// and the echo, but keeps the signal processing. Input is available
+    /// key by key and `Ctrl+C` still terminates the program.
+    ///
+    /// The cbreak mode is disabled in the same way as the raw mode. It fails with the
+    /// [`ScreenError::ModeMismatch`](enum.ScreenError.html#variant.ModeMismatch) error
+    /// if the raw mode is already enabled. It's a shortcut for the
+    /// [`into_raw_mode_with`](struct.RawScreen.html#method.into_raw_mode_with) method
+    /// with the [`RawModeOptions::cbreak`](struct.RawModeOptions.html#method.cbreak) options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let _cbreak = RawScreen::into_cbreak_mode()?;
+    ///     // Read key by key, Ctrl+C still works
+    ///     Ok(())
+    /// } // `_cbreak` is dropped here <- cbreak mode is disabled
+    /// ```
+    pub fn into_cbreak_mode() -> Result<RawScreen> {
+        RawScreen::into_raw_mode_with(RawModeOptions::cbreak())
+    }
+
+    /// Runs the given closure in the raw mode.
+    ///
+    /// The raw mode is enabled before the closure is called and it's disabled
+    /// when the closure returns, even if it panics (the panic is propagated
+    /// after the raw mode is disabled).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let answer = RawScreen::with(|| {
+    ///         // Do something in the raw mode
+    ///         42
+    ///     })?;
+    ///
+    ///     // Raw mode is disabled here
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with<R>(f: impl FnOnce() -> R) -> Result<R> {
+        let raw = RawScreen::into_raw_mode()?;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+
+        drop(raw);
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
     /// Disables raw mode.
+    ///
+    /// The raw mode is disabled even if there're other `RawScreen` values alive.
     pub fn disable_raw_mode() -> Result<()> {
-        #[cfg(unix)]
-        let mut command = sys::unix::RawModeCommand::new();
-        #[cfg(windows)]
-        let command = sys::winapi::RawModeCommand::new();
+        let mut command = sys::RawModeCommand::new()?;
+        let mut guards = lock_guards();
 
         command.disable()?;
+        // Keep it last - reset the counter only if we were able to switch back
+        guards.remove(&command.tty());
+        update_restore_registry(&guards);
+
         Ok(())
     }
 
+    /// Checks whether the raw mode is enabled.
+    ///
+    /// The current terminal (unix) or console (windows) mode is inspected, which
+    /// means that the raw mode enabled by someone else is detected as well.
+    pub fn is_raw_mode_enabled() -> Result<bool> {
+        sys::RawModeCommand::new()?.is_enabled()
+    }
+
+    /// Returns the current terminal input mode.
+    ///
+    /// The current terminal (unix) or console (windows) mode is inspected. It's
+    /// useful to detect whether the terminal is already in the raw mode when
+    /// the program starts (launched from another TUI for example).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::{RawScreen, TerminalMode};
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     if RawScreen::current_mode()? == TerminalMode::Cooked {
+    ///         let _raw = RawScreen::into_raw_mode()?;
+    ///         // Do something in the raw mode
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn current_mode() -> Result<TerminalMode> {
+        sys::RawModeCommand::new()?.current_mode()
+    }
+
     /// Keeps the raw mode enabled when `self` is dropped.
     ///
-    /// See the [`RawScreen`](struct.RawScreen.html) documentation for more
+    /// The raw mode stays enabled even if all the other `RawScreen` values are
+    /// dropped. See the [`RawScreen`](struct.RawScreen.html) documentation for more
     /// information.
     pub fn keep_raw_mode_on_drop(&mut self) {
-        self.disable_raw_mode_on_drop = false;
+        self.disable_raw_mode_on_drop(false);
+    }
+
+    /// Sets whether the raw mode is disabled when `self` is dropped.
+    ///
+    /// Unlike the [`keep_raw_mode_on_drop`](struct.RawScreen.html#method.keep_raw_mode_on_drop)
+    /// method, it allows to flip the behavior back on. It's useful when you hand
+    /// the `RawScreen` value over between components.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let mut raw = RawScreen::into_raw_mode()?;
+    ///     raw.disable_raw_mode_on_drop(false);
+    ///
+    ///     // Hand `raw` over to another component, which takes the responsibility back
+    ///     raw.disable_raw_mode_on_drop(true);
+    ///
+    ///     Ok(())
+    /// } // `raw` is dropped here <- raw mode is disabled
+    /// ```
+    pub fn disable_raw_mode_on_drop(&mut self, disable: bool) {
+        self.disable_raw_mode_on_drop = disable;
+    }
+
+    /// Temporarily switches back to the mode prior the raw mode.
+    ///
+    /// It's useful when you'd like to run another program (like `$EDITOR` or a pager)
+    /// in the cooked mode. Call the [`resume`](struct.RawScreen.html#method.resume)
+    /// method to switch back to the raw mode. The original mode is kept, dropping
+    /// the `RawScreen` value restores it no matter if the raw mode is suspended or not.
+    ///
+    /// The raw mode is suspended for all the `RawScreen` values of the same terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let raw = RawScreen::into_raw_mode()?;
+    ///
+    ///     raw.suspend()?;
+    ///     Command::new("vi").status()?;
+    ///     raw.resume()?;
+    ///
+    ///     // Back in the raw mode
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn suspend(&self) -> Result<()> {
+        let _guards = lock_guards();
+        self.command.suspend()
+    }
+
+    /// Switches back to the raw mode suspended with the
+    /// [`suspend`](struct.RawScreen.html#method.suspend) method.
+    pub fn resume(&self) -> Result<()> {
+        let _guards = lock_guards();
+        self.command.resume()
+    }
+
+    /// Returns the terminal attribute flags the raw mode changed.
+    ///
+    /// The changes are computed from the attributes prior the raw mode and the attributes
+    /// of the raw mode, the flag names are platform specific (like `ECHO` or
+    /// `ENABLE_ECHO_INPUT`). An empty list is returned if the raw mode was already
+    /// disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let raw = RawScreen::into_raw_mode()?;
+    ///
+    ///     // Prints something like "disabled ECHO, ICANON, ISIG" on UNIX
+    ///     let changes: Vec<String> = raw.changes().iter().map(|c| c.to_string()).collect();
+    ///     eprintln!("{}", changes.join(", "));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn changes(&self) -> Vec<AttributeChange> {
+        match self.command.raw_mode_attributes() {
+            Some((original, raw)) => {
+                TerminalAttributes::new(original).diff(&TerminalAttributes::new(raw))
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the file descriptor of the terminal the raw mode is applied to.
+    ///
+    /// It's the first standard stream which is a terminal (input, output, error) or
+    /// the controlling terminal (`/dev/tty`) when all of them are redirected.
+    ///
+    /// UNIX only.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crossterm_screen::RawScreen;
+    /// use crossterm_utils::Result;
+    ///
+    /// fn main() -> Result<()> {
+    ///     let raw = RawScreen::into_raw_mode()?;
+    ///
+    ///     if raw.terminal_fd() != libc::STDIN_FILENO {
+    ///         eprintln!("Standard input is redirected");
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(unix)]
+    pub fn terminal_fd(&self) -> RawFd {
+        self.command.tty()
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn command(&self) -> &sys::RawModeCommand {
+        &self.command
+    }
+
+    fn enable(command: sys::RawModeCommand) -> Result<RawScreen> {
+        RawScreen::enable_with(command, false, |_| {})
+    }
+
+    fn enable_with<F>(
+        mut command: sys::RawModeCommand,
+        exclusive: bool,
+        hook: F,
+    ) -> Result<RawScreen>
+    where
+        F: FnOnce(&mut sys::Attributes),
+    {
+        if share_raw_mode(&mut lock_guards(), &command, exclusive)? {
+            return Ok(RawScreen {
+                command,
+                disable_raw_mode_on_drop: true,
+                #[cfg(unix)]
+                job_control: false,
+            });
+        }
+
+        // The hook is called without the locks, a panicking hook would poison them and the
+        // restore panic hook would deadlock
+        let attributes = command.raw_attributes_with(hook)?;
+
+        let mut guards = lock_guards();
+
+        // Somebody else could enable the raw mode in the meantime
+        if !share_raw_mode(&mut guards, &command, exclusive)? {
+            command.enable(&attributes)?;
+            // Keep it last - count the guard only if we were able to switch to the raw mode
+            guards.insert(command.tty(), RawModeGuards { count: 1, command });
+            update_restore_registry(&guards);
+        }
+
+        Ok(RawScreen {
+            command,
+            disable_raw_mode_on_drop: true,
+            #[cfg(unix)]
+            job_control: false,
+        })
     }
 }
 
@@ -91,6 +506,17 @@ impl RawScreen {
 /// clear the screen, move the cursor and so on, so naturally you use the writer to change
 /// the mode as well.
 ///
+/// The trait is implemented for all the writers with a file descriptor (UNIX) or a handle
+/// (Windows). Standard streams share the raw mode of the terminal the process is attached
+/// to. Anything else (like a PTY) gets its own raw mode.
+///
+/// Use the standard error when the standard output is redirected (like `mytool | tee log`),
+/// see the [`AlternateScreen::to_alternate_stderr`](struct.AlternateScreen.html#method.to_alternate_stderr)
+/// method as well.
+///
+/// The writer is kept by the returned [`RawTerminal`](struct.RawTerminal.html), the file
+/// descriptor (handle) stays open until the raw mode is disabled.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -108,24 +534,113 @@ impl RawScreen {
 /// } // `_raw` dropped here <- raw mode disabled
 /// ```
 pub trait IntoRawMode: Write + Sized {
-    /// Enables raw mode.
-    fn into_raw_mode(self) -> Result<RawScreen>;
+    /// Enables raw mode and keeps the writer.
+    ///
+    /// See the [`RawTerminal`](struct.RawTerminal.html) documentation for more
+    /// information.
+    fn into_raw_mode(self) -> Result<RawTerminal<Self>>;
 }
 
-impl IntoRawMode for Stdout {
-    fn into_raw_mode(self) -> Result<RawScreen> {
-        RawScreen::into_raw_mode()?;
-        // this make's sure that raw screen will be disabled when it goes out of scope.
-        Ok(RawScreen {
-            disable_raw_mode_on_drop: true,
-        })
+#[cfg(unix)]
+impl<W: Write + AsRawFd> IntoRawMode for W {
+    fn into_raw_mode(self) -> Result<RawTerminal<Self>> {
+        let raw_screen = RawScreen::enable(sys::RawModeCommand::from_fd(self.as_raw_fd())?)?;
+        Ok(RawTerminal::new(self, raw_screen))
+    }
+}
+
+#[cfg(windows)]
+impl<W: Write + AsRawHandle> IntoRawMode for W {
+    fn into_raw_mode(self) -> Result<RawTerminal<Self>> {
+        let raw_screen =
+            RawScreen::enable(sys::RawModeCommand::from_handle(self.as_raw_handle())?)?;
+        Ok(RawTerminal::new(self, raw_screen))
     }
 }
 
 impl Drop for RawScreen {
     fn drop(&mut self) {
-        if self.disable_raw_mode_on_drop {
-            let _ = RawScreen::disable_raw_mode();
+        #[cfg(unix)]
+        {
+            if self.job_control {
+                crate::job_control::uninstall();
+            }
         }
+
+        if !self.disable_raw_mode_on_drop {
+            return;
+        }
+
+        let tty = self.command.tty();
+        let mut guards = lock_guards();
+
+        if let Some(enabled) = guards.get_mut(&tty) {
+            if enabled.count == 1 && self.command.disable().is_err() {
+                // Leave the counter untouched, the raw mode is still enabled
+                return;
+            }
+
+            enabled.count -= 1;
+
+            if enabled.count == 0 {
+                guards.remove(&tty);
+                update_restore_registry(&guards);
+            }
+        }
+    }
+}
+
+/// Disables raw mode of all the terminals regardless of the `RawScreen` values.
+pub(crate) fn disable_all_raw_modes() -> Result<()> {
+    let mut guards = lock_guards();
+
+    sys::RawModeCommand::disable_all()?;
+    // Keep it last - reset the counters only if we were able to switch back
+    guards.clear();
+    update_restore_registry(&guards);
+
+    Ok(())
+}
+
+// Forgets the raw mode of all the terminals regardless of the `RawScreen` values (the
+// terminal was replaced)
+#[cfg(feature = "recording")]
+pub(crate) fn forget_raw_modes() {
+    let mut guards = lock_guards();
+
+    guards.clear();
+    update_restore_registry(&guards);
+}
+
+// Counts another guard if the raw mode is already enabled, `false` if it's not
+fn share_raw_mode(
+    guards: &mut HashMap<sys::Tty, RawModeGuards>,
+    command: &sys::RawModeCommand,
+    exclusive: bool,
+) -> Result<bool> {
+    match guards.get_mut(&command.tty()) {
+        Some(_) if exclusive => Err(ScreenError::AlreadyRaw.into()),
+        Some(enabled) if !enabled.command.same_mode(command) => {
+            Err(ScreenError::ModeMismatch.into())
+        }
+        Some(enabled) => {
+            enabled.count += 1;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// Locks the raw mode guards, a poisoned lock is recovered (the counters are always consistent)
+fn lock_guards() -> MutexGuard<'static, HashMap<sys::Tty, RawModeGuards>> {
+    RAW_MODE_GUARDS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Keeps the restore registry in sync with the raw mode state
+fn update_restore_registry(guards: &HashMap<sys::Tty, RawModeGuards>) {
+    if guards.is_empty() {
+        restore::unregister(RAW_MODE_STATE_CHANGE);
+    } else {
+        restore::register(RAW_MODE_STATE_CHANGE, disable_all_raw_modes);
     }
 }
@@ -0,0 +1,76 @@
+use crossterm_utils::Result;
+
+use super::{RawModeOptions, RawScreen};
+use crate::sys;
+
+/// A guard which disables the input echo only.
+///
+/// Unlike the raw mode, the line buffering & editing, signals and the output
+/// processing are kept. The echo is enabled again when you drop the `NoEcho` value.
+/// It shares the reference counting with the [`RawScreen`](struct.RawScreen.html).
+///
+/// # Examples
+///
+/// Password prompt:
+///
+/// ```no_run
+/// use std::io::stdin;
+/// use crossterm_screen::NoEcho;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let mut password = String::new();
+///
+///     {
+///         let _no_echo = NoEcho::new()?;
+///         stdin().read_line(&mut password)?;
+///     } // `_no_echo` dropped here <- echo enabled
+///
+///     Ok(())
+/// }
+/// ```
+pub struct NoEcho {
+    _raw: RawScreen,
+}
+
+impl NoEcho {
+    /// Disables the input echo.
+    pub fn new() -> Result<NoEcho> {
+        let options = RawModeOptions::cooked().echo(false);
+
+        Ok(NoEcho {
+            _raw: RawScreen::into_raw_mode_with(options)?,
+        })
+    }
+}
+
+/// Disables the input echo.
+///
+/// Unlike the [`NoEcho`](struct.NoEcho.html) guard, the echo stays disabled until the
+/// [`enable_echo`](fn.enable_echo.html) function is called. The rest of the terminal mode
+/// is left untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{disable_echo, enable_echo};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     disable_echo()?;
+///
+///     // Read the password
+///
+///     enable_echo()
+/// }
+/// ```
+pub fn disable_echo() -> Result<()> {
+    sys::RawModeCommand::new()?.set_echo(false)
+}
+
+/// Enables the input echo.
+///
+/// See the [`disable_echo`](fn.disable_echo.html) function for more info.
+pub fn enable_echo() -> Result<()> {
+    sys::RawModeCommand::new()?.set_echo(true)
+}
@@ -0,0 +1,43 @@
+use crossterm_utils::Result;
+
+use super::{RawModeOptions, RawScreen};
+
+/// A guard which disables the software flow control only.
+///
+/// The software flow control (XON/XOFF) makes the terminal driver to interpret
+/// `Ctrl+S` (stop the output) and `Ctrl+Q` (resume the output). Disable it if you'd
+/// like to receive these keys (editors binding `Ctrl+S` to save for example) without
+/// enabling the raw mode. The flow control is enabled again when you drop the
+/// `NoFlowControl` value. It shares the reference counting with the
+/// [`RawScreen`](struct.RawScreen.html).
+///
+/// UNIX only, it does nothing on Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::NoFlowControl;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let _no_flow_control = NoFlowControl::new()?;
+///
+///     // Ctrl+S & Ctrl+Q are delivered to the application
+///
+///     Ok(())
+/// } // `_no_flow_control` dropped here <- flow control enabled
+/// ```
+pub struct NoFlowControl {
+    _raw: RawScreen,
+}
+
+impl NoFlowControl {
+    /// Disables the software flow control.
+    pub fn new() -> Result<NoFlowControl> {
+        let options = RawModeOptions::cooked().flow_control(false);
+
+        Ok(NoFlowControl {
+            _raw: RawScreen::into_raw_mode_with(options)?,
+        })
+    }
+}
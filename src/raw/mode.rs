@@ -0,0 +1,13 @@
+/// A terminal input mode.
+///
+/// See the [`RawScreen::current_mode`](struct.RawScreen.html#method.current_mode)
+/// method for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalMode {
+    /// The raw mode, the input is neither line buffered nor processed.
+    Raw,
+    /// The cbreak mode, the input is not line buffered, but signals are processed.
+    Cbreak,
+    /// The cooked (canonical) mode, the input is line buffered.
+    Cooked,
+}
@@ -0,0 +1,280 @@
+/// Options of the raw mode.
+///
+/// Every option says whether the terminal feature is kept enabled. The
+/// [`raw`](#method.raw) options (the default ones) disable all of them, the
+/// [`cbreak`](#method.cbreak) options disable the echo and the canonical mode only.
+///
+/// | Option | UNIX | Windows |
+/// | --- | --- | --- |
+/// | `echo` | `ECHO`, `ECHONL` | `ENABLE_ECHO_INPUT` |
+/// | `canonical` | `ICANON` | `ENABLE_LINE_INPUT` |
+/// | `signals` | `ISIG`, `IEXTEN`, `BRKINT` | `ENABLE_PROCESSED_INPUT` |
+/// | `flow_control` | `IXON`, `IXOFF` | - |
+/// | `output_processing` | `OPOST` (`ONLCR`, ...) | - |
+/// | `virtual_terminal_input` | - | `ENABLE_VIRTUAL_TERMINAL_INPUT` (set) |
+/// | `quick_edit` | - | `ENABLE_QUICK_EDIT_MODE` (`ENABLE_EXTENDED_FLAGS` set) |
+/// | `insert_mode` | - | `ENABLE_INSERT_MODE` (`ENABLE_EXTENDED_FLAGS` set) |
+/// | `mouse_input` | - | `ENABLE_MOUSE_INPUT` |
+/// | `read_minimum` | `VMIN` | - |
+/// | `read_timeout` | `VTIME` | - |
+/// | `job_control` | `SIGTSTP`, `SIGCONT` handlers | - |
+/// | `flush_input` | `TCSAFLUSH` | `FlushConsoleInputBuffer` |
+///
+/// # Examples
+///
+/// Read the input key by key without the echo, but let the terminal handle
+/// everything else:
+///
+/// ```no_run
+/// use crossterm_screen::{RawModeOptions, RawScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let options = RawModeOptions::raw()
+///         .signals(true)
+///         .flow_control(true)
+///         .output_processing(true);
+///
+///     let _raw = RawScreen::into_raw_mode_with(options)?;
+///
+///     // `println!` works, Ctrl+C terminates the program
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawModeOptions {
+    pub(crate) echo: bool,
+    pub(crate) canonical: bool,
+    pub(crate) signals: bool,
+    pub(crate) flow_control: bool,
+    pub(crate) output_processing: bool,
+    pub(crate) virtual_terminal_input: bool,
+    pub(crate) quick_edit: bool,
+    pub(crate) insert_mode: bool,
+    pub(crate) mouse_input: bool,
+    pub(crate) read_minimum: Option<u8>,
+    pub(crate) read_timeout: Option<u8>,
+    pub(crate) job_control: bool,
+    pub(crate) exclusive: bool,
+    pub(crate) flush_input: bool,
+}
+
+impl RawModeOptions {
+    /// Creates options of the raw mode (everything disabled).
+    pub fn raw() -> RawModeOptions {
+        RawModeOptions {
+            echo: false,
+            canonical: false,
+            signals: false,
+            flow_control: false,
+            output_processing: false,
+            virtual_terminal_input: false,
+            quick_edit: true,
+            insert_mode: true,
+            mouse_input: true,
+            read_minimum: None,
+            read_timeout: None,
+            job_control: false,
+            exclusive: false,
+            flush_input: false,
+        }
+    }
+
+    /// Creates options of the cbreak mode (echo and canonical mode disabled).
+    pub fn cbreak() -> RawModeOptions {
+        RawModeOptions {
+            echo: false,
+            canonical: false,
+            signals: true,
+            flow_control: true,
+            output_processing: true,
+            virtual_terminal_input: false,
+            quick_edit: true,
+            insert_mode: true,
+            mouse_input: true,
+            read_minimum: None,
+            read_timeout: None,
+            job_control: false,
+            exclusive: false,
+            flush_input: false,
+        }
+    }
+
+    /// Creates options of the cooked mode (everything enabled).
+    ///
+    /// It's a starting point for disabling single features, like the echo.
+    pub fn cooked() -> RawModeOptions {
+        RawModeOptions::cbreak().echo(true).canonical(true)
+    }
+
+    /// Creates options of the raw input mode (raw mode with the output processing kept).
+    ///
+    /// The input is raw, but the output processing (`ONLCR`) stays enabled, so `\n`
+    /// is still translated to `\r\n` and `println!` output doesn't staircase. Most TUI
+    /// applications care about the input rawness only.
+    pub fn raw_input() -> RawModeOptions {
+        RawModeOptions::raw().output_processing(true)
+    }
+
+    /// Keeps the input echo enabled.
+    pub fn echo(mut self, enabled: bool) -> RawModeOptions {
+        self.echo = enabled;
+        self
+    }
+
+    /// Keeps the canonical mode (line buffering & editing) enabled.
+    pub fn canonical(mut self, enabled: bool) -> RawModeOptions {
+        self.canonical = enabled;
+        self
+    }
+
+    /// Keeps the signal processing (`Ctrl+C`, `Ctrl+Z`, ...) enabled.
+    pub fn signals(mut self, enabled: bool) -> RawModeOptions {
+        self.signals = enabled;
+        self
+    }
+
+    /// Keeps the software flow control (`Ctrl+S`, `Ctrl+Q`) enabled.
+    ///
+    /// UNIX only, ignored on Windows.
+    pub fn flow_control(mut self, enabled: bool) -> RawModeOptions {
+        self.flow_control = enabled;
+        self
+    }
+
+    /// Keeps the output processing (`\n` to `\r\n` translation, ...) enabled.
+    ///
+    /// UNIX only, ignored on Windows.
+    pub fn output_processing(mut self, enabled: bool) -> RawModeOptions {
+        self.output_processing = enabled;
+        self
+    }
+
+    /// Enables the virtual terminal input (keys are reported as VT escape sequences).
+    ///
+    /// Unlike other options, it enables a feature which is disabled by default. It's
+    /// disabled again when the raw mode is disabled.
+    ///
+    /// Windows 10+ only, ignored on UNIX (keys are always reported as escape sequences).
+    pub fn virtual_terminal_input(mut self, enabled: bool) -> RawModeOptions {
+        self.virtual_terminal_input = enabled;
+        self
+    }
+
+    /// Keeps the Quick Edit mode (text selection with the mouse) enabled.
+    ///
+    /// Unlike other options, it's kept enabled by the [`raw`](#method.raw) options too.
+    /// Disable it if you'd like to receive the mouse events, an accidental selection
+    /// freezes the output otherwise. It's enabled again when the raw mode is disabled.
+    ///
+    /// Windows only, ignored on UNIX.
+    pub fn quick_edit(mut self, enabled: bool) -> RawModeOptions {
+        self.quick_edit = enabled;
+        self
+    }
+
+    /// Keeps the insert mode (typed text is inserted, not overwritten) enabled.
+    ///
+    /// Like the [`quick_edit`](#method.quick_edit) option, it's kept enabled by the
+    /// [`raw`](#method.raw) options too.
+    ///
+    /// Windows only, ignored on UNIX.
+    pub fn insert_mode(mut self, enabled: bool) -> RawModeOptions {
+        self.insert_mode = enabled;
+        self
+    }
+
+    /// Keeps the mouse input (mouse events in the input buffer) as it is.
+    ///
+    /// Like the [`quick_edit`](#method.quick_edit) option, it's kept by the
+    /// [`raw`](#method.raw) options too. Pass `false` to clear the `ENABLE_MOUSE_INPUT`
+    /// flag while the raw mode is enabled.
+    ///
+    /// Windows only, ignored on UNIX.
+    pub fn mouse_input(mut self, enabled: bool) -> RawModeOptions {
+        self.mouse_input = enabled;
+        self
+    }
+
+    /// Sets the minimum number of bytes a read waits for (`VMIN`).
+    ///
+    /// Combined with the [`read_timeout`](#method.read_timeout) it allows to choose
+    /// between blocking and timed reads:
+    ///
+    /// * `VMIN > 0`, `VTIME == 0` - blocks until `VMIN` bytes are available
+    /// * `VMIN == 0`, `VTIME > 0` - waits up to `VTIME` for a byte
+    /// * `VMIN > 0`, `VTIME > 0` - waits for `VMIN` bytes, `VTIME` is an inter-byte timeout
+    /// * `VMIN == 0`, `VTIME == 0` - never blocks
+    ///
+    /// UNIX only, ignored on Windows.
+    pub fn read_minimum(mut self, bytes: u8) -> RawModeOptions {
+        self.read_minimum = Some(bytes);
+        self
+    }
+
+    /// Sets the read timeout in tenths of a second (`VTIME`).
+    ///
+    /// See the [`read_minimum`](#method.read_minimum) method for more information.
+    ///
+    /// UNIX only, ignored on Windows.
+    pub fn read_timeout(mut self, deciseconds: u8) -> RawModeOptions {
+        self.read_timeout = Some(deciseconds);
+        self
+    }
+
+    /// Installs the job control (`SIGTSTP` & `SIGCONT`) handlers.
+    ///
+    /// When the program is suspended (`Ctrl+Z`), the mode prior the raw mode is
+    /// restored and the main screen is shown. When it's resumed (`fg`), the raw mode
+    /// and the alternate screen are switched back. The terminal sends `SIGTSTP` on
+    /// `Ctrl+Z` only if the signals are kept, you can send it yourself otherwise.
+    ///
+    /// The handlers stay installed when the raw mode is disabled. The previous actions are
+    /// restored when the last `RawScreen` value created with the job control is dropped.
+    ///
+    /// UNIX only, ignored on Windows.
+    pub fn job_control(mut self, enabled: bool) -> RawModeOptions {
+        self.job_control = enabled;
+        self
+    }
+
+    /// Discards the pending input when the raw mode is enabled or disabled.
+    ///
+    /// By default, the mode is changed immediately (`TCSANOW`) and the keys typed
+    /// before are preserved, which is what shells want. Games usually want them
+    /// discarded (`TCSAFLUSH`). The same applies to the
+    /// [`suspend`](struct.RawScreen.html#method.suspend) &
+    /// [`resume`](struct.RawScreen.html#method.resume) methods.
+    pub fn flush_input(mut self, enabled: bool) -> RawModeOptions {
+        self.flush_input = enabled;
+        self
+    }
+
+    /// Fails if the raw mode is already enabled.
+    ///
+    /// By default, the raw mode is shared. Enabling it again is a noop and returns
+    /// another `RawScreen` value, the raw mode is disabled when the last one is dropped.
+    /// The exclusive raw mode fails with the
+    /// [`ScreenError::AlreadyRaw`](enum.ScreenError.html#variant.AlreadyRaw) error
+    /// instead.
+    pub fn exclusive(mut self, enabled: bool) -> RawModeOptions {
+        self.exclusive = enabled;
+        self
+    }
+
+    // Checks whether all the terminal features are disabled (the real raw mode)
+    pub(crate) fn disables_everything(&self) -> bool {
+        !self.echo
+            && !self.canonical
+            && !self.signals
+            && !self.flow_control
+            && !self.output_processing
+    }
+}
+
+impl Default for RawModeOptions {
+    fn default() -> Self {
+        RawModeOptions::raw()
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+
+use super::RawScreen;
+
+/// A writer in the raw mode.
+///
+/// Unlike the [`RawScreen`](struct.RawScreen.html), it keeps the writer, dereferences
+/// to it and implements the `Write` trait, so you can keep writing through the same
+/// value. The raw mode is disabled when you drop the `RawTerminal` value.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::IntoRawMode;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let mut stdout = stdout().into_raw_mode()?;
+///
+///     write!(stdout, "Hello in the raw mode\r\n")?;
+///     stdout.flush()?;
+///
+///     Ok(())
+/// } // `stdout` dropped here <- raw mode disabled
+/// ```
+pub struct RawTerminal<W: Write> {
+    // Keep it first - the raw mode is disabled before the writer is dropped (a file
+    // descriptor or a handle is closed)
+    raw_screen: RawScreen,
+    writer: W,
+}
+
+impl<W: Write> RawTerminal<W> {
+    pub(crate) fn new(writer: W, raw_screen: RawScreen) -> RawTerminal<W> {
+        RawTerminal { raw_screen, writer }
+    }
+
+    /// Returns the raw screen, to suspend the raw mode for example.
+    pub fn raw_screen(&mut self) -> &mut RawScreen {
+        &mut self.raw_screen
+    }
+}
+
+impl<W: Write> Drop for RawTerminal<W> {
+    fn drop(&mut self) {
+        // The buffered output is written before the raw mode is disabled
+        let _ = self.writer.flush();
+    }
+}
+
+impl<W: Write> Deref for RawTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.writer
+    }
+}
+
+impl<W: Write> DerefMut for RawTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: Write> Write for RawTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,497 @@
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+#[cfg(windows)]
+use std::os::windows::io::RawHandle;
+use std::str;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crossterm_utils::{Command, Result};
+use lazy_static::lazy_static;
+#[cfg(windows)]
+use winapi::shared::minwindef::DWORD;
+#[cfg(windows)]
+use winapi::um::wincon::{CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_LINE_INPUT};
+
+#[cfg(unix)]
+use crate::sys::unix::{self, TerminalBackend};
+#[cfg(windows)]
+use crate::sys::winapi::{console, set_console_backend, ConsoleBackend};
+#[cfg(windows)]
+use crate::FakeConsole;
+use crate::{alternate, raw, AlternateScreen, RawScreen};
+
+lazy_static! {
+    // The events of the `RecordingScreen`, `None` if nothing is recorded
+    static ref RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+    // `true` while a `RecordingScreen` exists, there's at most one at a time
+    static ref RECORDING: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+}
+
+/// An event recorded by the [`RecordingScreen`](struct.RecordingScreen.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RecordedEvent {
+    /// The sequence (or the text) written to the terminal.
+    Write(String),
+    /// The raw (or the cbreak) mode was enabled, the input is not line buffered.
+    RawModeEnabled,
+    /// The raw (or the cbreak) mode was disabled, the input is line buffered again.
+    RawModeDisabled,
+    /// The alternate screen was entered.
+    AlternateScreenEntered,
+    /// The main screen was shown.
+    AlternateScreenLeft,
+}
+
+/// A screen recording everything instead of changing the terminal.
+///
+/// The real alternate screen & raw mode code runs (see
+/// [`AlternateScreen`](struct.AlternateScreen.html) and
+/// [`RawScreen`](struct.RawScreen.html)), including the nesting, the
+/// [`builder`](struct.AlternateScreen.html#method.builder) features, the flavor detection
+/// (`TERM=screen`), the [`DumbTerminalPolicy`](enum.DumbTerminalPolicy.html), and the
+/// disabled alternate screen. The sequences written to the standard output & error and the
+/// mode changes are recorded into an inspectable log, the terminal is never touched. It
+/// allows to unit test the screen handling without a real terminal. Everything written to
+/// it (it's a `Write`) is recorded as well.
+///
+/// The raw mode is applied to an in-memory terminal (UNIX) or a
+/// [`FakeConsole`](struct.FakeConsole.html) (Windows) in the cooked mode. The legacy
+/// Windows console (no ANSI support, see the
+/// [`force_ansi`](fn.force_ansi.html) function) switches the screen buffers of the
+/// fake console, only the mode changes are recorded then.
+///
+/// The state is global, there's at most one recording screen at a time (the `new` function
+/// waits for the previous one to be dropped). The recording starts on the main screen in
+/// the cooked mode, the alternate screen & raw mode tracked so far are forgotten. Create it
+/// when neither the alternate screen nor the raw mode is enabled. Everything entered with
+/// the recording screen methods is reverted (and recorded) when it's dropped.
+///
+/// Available with the `recording` feature only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{RecordedEvent, RecordingScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let mut screen = RecordingScreen::new();
+///
+///     screen.to_alternate(true)?;
+///     screen.to_main()?;
+///
+///     assert!(!screen.is_alternate_screen());
+///     assert_eq!(screen.events()[2], RecordedEvent::RawModeEnabled);
+///     assert!(screen.output().contains("\x1B[?1049h"));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct RecordingScreen {
+    // The alternate screens entered with `to_alternate` (the innermost one last)
+    alternate_screens: Vec<AlternateScreen>,
+    // The raw screens of `enable_raw_mode`
+    raw_screens: Vec<RawScreen>,
+    // The terminal replaced by the recorded one, it's used again when the recording ends
+    #[cfg(unix)]
+    terminal: Arc<dyn TerminalBackend>,
+    #[cfg(windows)]
+    console: Arc<dyn ConsoleBackend>,
+}
+
+impl RecordingScreen {
+    /// Creates a recording screen, the main screen is shown in the cooked mode.
+    ///
+    /// It waits until the previous recording screen is dropped.
+    pub fn new() -> RecordingScreen {
+        let (recording, dropped) = &*RECORDING;
+        let mut recording = recording.lock().unwrap_or_else(|e| e.into_inner());
+
+        while *recording {
+            recording = dropped.wait(recording).unwrap_or_else(|e| e.into_inner());
+        }
+
+        *recording = true;
+
+        forget_screen_state();
+
+        let screen = RecordingScreen {
+            alternate_screens: Vec::new(),
+            raw_screens: Vec::new(),
+            #[cfg(unix)]
+            terminal: unix::set_terminal_backend(Arc::new(RecordedTerminal::new())),
+            #[cfg(windows)]
+            console: replace_console(Arc::new(RecordedConsole::new())),
+        };
+
+        *lock_recorder() = Some(Recorder::default());
+        screen
+    }
+
+    /// Switches to the alternate screen with the
+    /// [`AlternateScreen::to_alternate`](struct.AlternateScreen.html#method.to_alternate)
+    /// function.
+    ///
+    /// The alternate screen is kept until the [`to_main`](#method.to_main) method is
+    /// called. The alternate screens are nested, see the
+    /// [`AlternateScreen`](struct.AlternateScreen.html) documentation.
+    pub fn to_alternate(&mut self, raw_mode: bool) -> Result<()> {
+        self.alternate_screens
+            .push(AlternateScreen::to_alternate(raw_mode)?);
+        Ok(())
+    }
+
+    /// Drops the innermost alternate screen of the [`to_alternate`](#method.to_alternate)
+    /// method, its raw mode is disabled first.
+    ///
+    /// The main screen is shown when the outermost alternate screen is left. It's a noop
+    /// if there's no alternate screen.
+    pub fn to_main(&mut self) -> Result<()> {
+        if let Some(mut alternate) = self.alternate_screens.pop() {
+            // The raw mode is disabled before the main screen is shown, like on drop
+            drop(alternate.raw_screen.take());
+            alternate.to_main()?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables the raw mode with the
+    /// [`RawScreen::into_raw_mode`](struct.RawScreen.html#method.into_raw_mode) function.
+    ///
+    /// The raw mode is kept until the [`disable_raw_mode`](#method.disable_raw_mode)
+    /// method is called, the `RawScreen` values are reference counted.
+    pub fn enable_raw_mode(&mut self) -> Result<()> {
+        self.raw_screens.push(RawScreen::into_raw_mode()?);
+        Ok(())
+    }
+
+    /// Drops a raw screen of the [`enable_raw_mode`](#method.enable_raw_mode) method.
+    ///
+    /// The raw mode is disabled when the last `RawScreen` value is dropped. It's a noop
+    /// if there's no raw screen.
+    pub fn disable_raw_mode(&mut self) -> Result<()> {
+        self.raw_screens.pop();
+        Ok(())
+    }
+
+    /// Records the ANSI sequence of the command.
+    ///
+    /// The sequence is recorded as a write. Nothing is tracked, the alternate screen
+    /// commands don't change the nesting depth and the [`restore_all`](fn.restore_all.html)
+    /// registry is left untouched. Pass the recording screen to the `execute_tracked`
+    /// methods (like [`PushTitle::execute_tracked`](struct.PushTitle.html#method.execute_tracked))
+    /// to track the state as well.
+    pub fn execute<C>(&mut self, command: C) -> Result<()>
+    where
+        C: Command,
+        C::AnsiType: Display,
+    {
+        self.write_all(command.ansi_code().to_string().as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the recorded events (oldest first).
+    ///
+    /// An incomplete UTF-8 character written last is not recorded until it's completed.
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        lock_recorder()
+            .as_ref()
+            .map(|recorder| recorder.events.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns everything written so far (all the `Write` events concatenated).
+    pub fn output(&self) -> String {
+        self.events()
+            .iter()
+            .filter_map(|event| match event {
+                RecordedEvent::Write(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forgets the recorded events, the screen state is kept.
+    pub fn clear(&mut self) {
+        if let Some(recorder) = lock_recorder().as_mut() {
+            recorder.events.clear();
+        }
+    }
+
+    /// Checks whether the alternate screen is shown.
+    ///
+    /// See the [`AlternateScreen::is_active`](struct.AlternateScreen.html#method.is_active)
+    /// method.
+    pub fn is_alternate_screen(&self) -> bool {
+        AlternateScreen::is_active()
+    }
+
+    /// Checks whether the raw mode is enabled.
+    ///
+    /// See the [`RawScreen::is_raw_mode_enabled`](struct.RawScreen.html#method.is_raw_mode_enabled)
+    /// method.
+    pub fn is_raw_mode_enabled(&self) -> bool {
+        RawScreen::is_raw_mode_enabled().unwrap_or(false)
+    }
+}
+
+impl Default for RecordingScreen {
+    fn default() -> RecordingScreen {
+        RecordingScreen::new()
+    }
+}
+
+impl fmt::Debug for RecordingScreen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingScreen")
+            .field("events", &self.events())
+            .finish()
+    }
+}
+
+impl Write for RecordingScreen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(recorder) = lock_recorder().as_mut() {
+            recorder.write(buf);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecordingScreen {
+    fn drop(&mut self) {
+        // The innermost alternate screen is left first, the raw modes are disabled by then
+        while let Some(alternate) = self.alternate_screens.pop() {
+            drop(alternate);
+        }
+        self.raw_screens.clear();
+
+        lock_recorder().take();
+        forget_screen_state();
+
+        #[cfg(unix)]
+        unix::set_terminal_backend(self.terminal.clone());
+        #[cfg(windows)]
+        replace_console(self.console.clone());
+
+        let (recording, dropped) = &*RECORDING;
+        *recording.lock().unwrap_or_else(|e| e.into_inner()) = false;
+        dropped.notify_one();
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<RecordedEvent>,
+    // The incomplete UTF-8 character written last, it's completed by the next write
+    pending: Vec<u8>,
+}
+
+impl Recorder {
+    // Records the bytes as a write, an incomplete character at the end is kept for later
+    fn write(&mut self, buf: &[u8]) {
+        self.pending.extend_from_slice(buf);
+
+        let complete = self.pending.len() - incomplete_suffix(&self.pending);
+        if complete == 0 {
+            return;
+        }
+
+        let text = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        self.events.push(RecordedEvent::Write(text));
+    }
+}
+
+// Returns the length of the incomplete UTF-8 character at the end of the bytes
+fn incomplete_suffix(bytes: &[u8]) -> usize {
+    (1..=bytes.len().min(3))
+        .find(
+            |length| match str::from_utf8(&bytes[bytes.len() - length..]) {
+                Err(e) => e.valid_up_to() == 0 && e.error_len().is_none(),
+                Ok(_) => false,
+            },
+        )
+        .unwrap_or(0)
+}
+
+// Locks the recorder, a poisoned lock is recovered (the events are always consistent)
+fn lock_recorder() -> MutexGuard<'static, Option<Recorder>> {
+    RECORDER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Records the sequence written by this crate, `false` if there's no recording screen (the
+// sequence must be written to the terminal)
+pub(crate) fn capture(sequence: &str) -> bool {
+    match lock_recorder().as_mut() {
+        Some(recorder) => {
+            recorder.write(sequence.as_bytes());
+            true
+        }
+        None => false,
+    }
+}
+
+// Records the event if there's a recording screen
+pub(crate) fn record(event: RecordedEvent) {
+    if let Some(recorder) = lock_recorder().as_mut() {
+        recorder.events.push(event);
+    }
+}
+
+// Forgets the alternate screen & raw mode state, the terminal is replaced
+fn forget_screen_state() {
+    alternate::forget_nesting();
+    raw::forget_raw_modes();
+}
+
+// Records the switch between the line buffered input and the raw (or cbreak) mode
+fn record_line_input(was_line_input: bool, line_input: bool) {
+    if was_line_input && !line_input {
+        record(RecordedEvent::RawModeEnabled);
+    } else if !was_line_input && line_input {
+        record(RecordedEvent::RawModeDisabled);
+    }
+}
+
+// The in-memory terminal of the standard input, it starts in the cooked mode
+#[cfg(unix)]
+struct RecordedTerminal {
+    attributes: Mutex<libc::termios>,
+}
+
+#[cfg(unix)]
+impl RecordedTerminal {
+    fn new() -> RecordedTerminal {
+        let mut attributes: libc::termios = unsafe { std::mem::zeroed() };
+        attributes.c_iflag = libc::ICRNL | libc::IXON;
+        attributes.c_oflag = libc::OPOST | libc::ONLCR;
+        attributes.c_cflag = libc::CS8 | libc::CREAD;
+        attributes.c_lflag = libc::ICANON | libc::ECHO | libc::ECHOE | libc::ISIG | libc::IEXTEN;
+
+        RecordedTerminal {
+            attributes: Mutex::new(attributes),
+        }
+    }
+
+    fn check_tty(&self, fd: libc::c_int) -> Result<()> {
+        if self.is_tty(fd) {
+            Ok(())
+        } else {
+            Err(io::Error::from_raw_os_error(libc::ENOTTY).into())
+        }
+    }
+}
+
+#[cfg(unix)]
+impl TerminalBackend for RecordedTerminal {
+    fn is_tty(&self, fd: libc::c_int) -> bool {
+        fd == libc::STDIN_FILENO
+    }
+
+    fn attributes(&self, fd: libc::c_int) -> Result<libc::termios> {
+        self.check_tty(fd)?;
+        Ok(*self.attributes.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn set_attributes(
+        &self,
+        fd: libc::c_int,
+        _action: libc::c_int,
+        termios: &libc::termios,
+    ) -> Result<()> {
+        self.check_tty(fd)?;
+
+        let mut attributes = self.attributes.lock().unwrap_or_else(|e| e.into_inner());
+        let line_input = |attributes: &libc::termios| attributes.c_lflag & libc::ICANON != 0;
+
+        record_line_input(line_input(&attributes), line_input(termios));
+        *attributes = *termios;
+        Ok(())
+    }
+}
+
+// Replaces the console and returns the previous one
+#[cfg(windows)]
+fn replace_console(backend: Arc<dyn ConsoleBackend>) -> Arc<dyn ConsoleBackend> {
+    let previous = console();
+    set_console_backend(Some(backend));
+    previous
+}
+
+// The fake console, the mode changes of the console input are recorded
+#[cfg(windows)]
+struct RecordedConsole {
+    console: FakeConsole,
+}
+
+#[cfg(windows)]
+impl RecordedConsole {
+    fn new() -> RecordedConsole {
+        RecordedConsole {
+            console: FakeConsole::new(),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl ConsoleBackend for RecordedConsole {
+    fn input_handle(&self) -> Result<RawHandle> {
+        self.console.input_handle()
+    }
+
+    fn output_handle(&self) -> Result<RawHandle> {
+        self.console.output_handle()
+    }
+
+    fn mode(&self, handle: RawHandle) -> Result<DWORD> {
+        self.console.mode(handle)
+    }
+
+    fn set_mode(&self, handle: RawHandle, mode: DWORD) -> Result<()> {
+        let previous = self.console.mode(handle)?;
+        self.console.set_mode(handle, mode)?;
+
+        if handle == self.console.input_handle()? {
+            let line_input = |mode: DWORD| mode & ENABLE_LINE_INPUT != 0;
+            record_line_input(line_input(previous), line_input(mode));
+        }
+
+        Ok(())
+    }
+
+    fn flush_input(&self, handle: RawHandle) -> Result<()> {
+        self.console.flush_input(handle)
+    }
+
+    fn create_screen_buffer(&self) -> Result<RawHandle> {
+        self.console.create_screen_buffer()
+    }
+
+    fn show_screen_buffer(&self, handle: RawHandle) -> Result<()> {
+        self.console.show_screen_buffer(handle)
+    }
+
+    fn screen_buffer_info(&self, handle: RawHandle) -> Result<CONSOLE_SCREEN_BUFFER_INFO> {
+        self.console.screen_buffer_info(handle)
+    }
+
+    fn set_screen_buffer_size(&self, handle: RawHandle, size: COORD) -> Result<()> {
+        self.console.set_screen_buffer_size(handle, size)
+    }
+
+    fn restore_screen_buffer(
+        &self,
+        handle: RawHandle,
+        info: &CONSOLE_SCREEN_BUFFER_INFO,
+    ) -> Result<()> {
+        self.console.restore_screen_buffer(handle, info)
+    }
+}
@@ -0,0 +1,292 @@
+//! Terminal resize notifications.
+//!
+//! A `SIGWINCH` handler is installed on UNIX. The self-pipe trick is used - the handler
+//! writes a byte to a pipe and a background thread sends the new size to the receivers.
+//! A background thread watches the console input for the `WINDOW_BUFFER_SIZE_EVENT` on
+//! Windows.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard};
+
+use crossterm_utils::Result;
+use lazy_static::lazy_static;
+
+#[cfg(windows)]
+use self::console::install;
+#[cfg(unix)]
+use self::unix::install;
+
+lazy_static! {
+    // Senders of all the receivers, the disconnected ones are removed on the next resize
+    static ref SUBSCRIBERS: Mutex<Vec<Sender<(u16, u16)>>> = Mutex::new(Vec::new());
+    // `true` if the resize watcher is installed
+    static ref INSTALLED: Mutex<bool> = Mutex::new(false);
+}
+
+// Locks the senders, a poisoned lock is recovered (the senders are always consistent)
+fn lock_subscribers() -> MutexGuard<'static, Vec<Sender<(u16, u16)>>> {
+    SUBSCRIBERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns a receiver of the terminal size `(columns, rows)` changes.
+///
+/// The new size is sent whenever the terminal window is resized (`SIGWINCH` on UNIX,
+/// `WINDOW_BUFFER_SIZE_EVENT` on Windows). The resize watcher is installed with the
+/// first call and it's never uninstalled, every call returns a new receiver. Drop the
+/// receiver to stop the notifications.
+///
+/// The `SIGWINCH` handler installed before (if any) is still called on UNIX.
+///
+/// # Notes
+///
+/// The console input events are peeked only on Windows, they're left for the input
+/// readers. The size is checked periodically as well, the `WINDOW_BUFFER_SIZE_EVENT`
+/// is reported only if the `ENABLE_WINDOW_INPUT` mode is set.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_utils::Result;
+/// use crossterm_screen::resize_events;
+///
+/// fn main() -> Result<()> {
+///     let resizes = resize_events()?;
+///
+///     for (columns, rows) in resizes {
+///         println!("{}x{}", columns, rows);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn resize_events() -> Result<Receiver<(u16, u16)>> {
+    {
+        // A poisoned lock is recovered (the flag is always consistent)
+        let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !*installed {
+            install()?;
+            // Keep it last - mark it as installed only if we were able to install it
+            *installed = true;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    lock_subscribers().push(sender);
+
+    Ok(receiver)
+}
+
+// Sends the size to all the receivers
+fn notify(size: (u16, u16)) {
+    lock_subscribers().retain(|sender| sender.send(size).is_ok());
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io;
+    use std::os::unix::io::RawFd;
+    use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+    use std::{mem, ptr, thread};
+
+    use crossterm_utils::Result;
+    use libc::{c_int, c_void, siginfo_t, SA_RESTART, SA_SIGINFO, SIGWINCH, SIG_DFL, SIG_IGN};
+
+    use crate::sys::unix::wrap_with_result;
+
+    // Write end of the pipe, -1 if the handler is not installed
+    static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+    // The `SIGWINCH` handler prior ours (`SIG_DFL` if there's none) and whether it takes
+    // the signal info (`SA_SIGINFO`)
+    static PREVIOUS_HANDLER: AtomicUsize = AtomicUsize::new(SIG_DFL);
+    static PREVIOUS_SIGINFO: AtomicBool = AtomicBool::new(false);
+
+    // Installs the `SIGWINCH` handler, the previous one is called from it
+    pub(super) fn install() -> Result<()> {
+        let mut fds = [0 as RawFd; 2];
+        wrap_with_result(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+        let (read_fd, write_fd) = (fds[0], fds[1]);
+
+        // Never block in the signal handler
+        if let Err(error) =
+            wrap_with_result(unsafe { libc::fcntl(write_fd, libc::F_SETFL, libc::O_NONBLOCK) })
+        {
+            close_pipe(read_fd, write_fd);
+            return Err(error);
+        }
+
+        SIGNAL_PIPE.store(write_fd, Ordering::SeqCst);
+
+        // The signals received before the thread is spawned wait in the pipe
+        let previous = match set_handler() {
+            Ok(previous) => previous,
+            Err(error) => {
+                close_pipe(read_fd, write_fd);
+                return Err(error);
+            }
+        };
+
+        let spawned = thread::Builder::new()
+            .name("crossterm-resize".to_string())
+            .spawn(move || handle_signals(read_fd));
+
+        if let Err(error) = spawned {
+            unsafe {
+                libc::sigaction(SIGWINCH, &previous, ptr::null_mut());
+            }
+            close_pipe(read_fd, write_fd);
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    // Installs the handler, returns the previous action
+    fn set_handler() -> Result<libc::sigaction> {
+        unsafe {
+            // The previous handler must be known before ours can be called
+            let mut previous: libc::sigaction = mem::zeroed();
+            wrap_with_result(libc::sigaction(SIGWINCH, ptr::null(), &mut previous))?;
+            PREVIOUS_HANDLER.store(previous.sa_sigaction, Ordering::SeqCst);
+            PREVIOUS_SIGINFO.store(previous.sa_flags & SA_SIGINFO != 0, Ordering::SeqCst);
+
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(c_int, *mut siginfo_t, *mut c_void)
+                as libc::sighandler_t;
+            action.sa_flags = SA_RESTART | SA_SIGINFO;
+            libc::sigemptyset(&mut action.sa_mask);
+            wrap_with_result(libc::sigaction(SIGWINCH, &action, ptr::null_mut()))?;
+
+            Ok(previous)
+        }
+    }
+
+    // Closes the pipe which was not handed over to the thread
+    fn close_pipe(read_fd: RawFd, write_fd: RawFd) {
+        SIGNAL_PIPE.store(-1, Ordering::SeqCst);
+
+        unsafe {
+            libc::close(read_fd);
+            libc::close(write_fd);
+        }
+    }
+
+    extern "C" fn on_signal(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+        let byte = 0u8;
+        let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+
+        // `write` is async-signal-safe, the byte is dropped if the pipe is full
+        unsafe {
+            libc::write(fd, &byte as *const u8 as *const c_void, 1);
+        }
+
+        let previous = PREVIOUS_HANDLER.load(Ordering::SeqCst);
+
+        if previous == SIG_DFL || previous == SIG_IGN {
+            return;
+        }
+
+        unsafe {
+            if PREVIOUS_SIGINFO.load(Ordering::SeqCst) {
+                let handler: extern "C" fn(c_int, *mut siginfo_t, *mut c_void) =
+                    mem::transmute(previous);
+                handler(signal, info, context);
+            } else {
+                let handler: extern "C" fn(c_int) = mem::transmute(previous);
+                handler(signal);
+            }
+        }
+    }
+
+    fn handle_signals(read_fd: RawFd) {
+        let mut byte = 0u8;
+
+        loop {
+            let read = unsafe { libc::read(read_fd, &mut byte as *mut u8 as *mut c_void, 1) };
+
+            match read {
+                1 => {
+                    if let Ok(size) = crate::size() {
+                        super::notify(size);
+                    }
+                }
+                -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+                _ => return,
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod console {
+    use std::time::Duration;
+    use std::{mem, thread};
+
+    use crossterm_utils::Result;
+    use crossterm_winapi::Handle;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::consoleapi::PeekConsoleInputW;
+    use winapi::um::synchapi::WaitForSingleObject;
+    use winapi::um::winbase::WAIT_OBJECT_0;
+    use winapi::um::wincontypes::{INPUT_RECORD, WINDOW_BUFFER_SIZE_EVENT};
+    use winapi::um::winnt::HANDLE;
+
+    // How long to wait for the console input events before the size is checked anyway
+    const POLL_INTERVAL_MS: DWORD = 100;
+
+    // How many pending input events are peeked
+    const PEEKED_EVENTS: usize = 64;
+
+    // Starts the console input watcher
+    pub(super) fn install() -> Result<()> {
+        // The handle address is passed to keep the closure `Send`
+        let input = Handle::input_handle()? as usize;
+
+        thread::Builder::new()
+            .name("crossterm-resize".to_string())
+            .spawn(move || watch_console(input as HANDLE))?;
+
+        Ok(())
+    }
+
+    fn watch_console(input: HANDLE) {
+        let mut last_size = crate::size().ok();
+
+        loop {
+            let signaled = unsafe { WaitForSingleObject(input, POLL_INTERVAL_MS) } == WAIT_OBJECT_0;
+
+            // The handle stays signaled until the input readers read the events, do not spin
+            if signaled && !has_resize_event(input) {
+                thread::sleep(Duration::from_millis(POLL_INTERVAL_MS as u64));
+            }
+
+            if let Ok(size) = crate::size() {
+                if last_size != Some(size) {
+                    last_size = Some(size);
+                    super::notify(size);
+                }
+            }
+        }
+    }
+
+    // Checks whether there's a `WINDOW_BUFFER_SIZE_EVENT` among the pending input events
+    fn has_resize_event(input: HANDLE) -> bool {
+        let mut records: [INPUT_RECORD; PEEKED_EVENTS] = unsafe { mem::zeroed() };
+        let mut count: DWORD = 0;
+
+        let result = unsafe {
+            PeekConsoleInputW(
+                input,
+                records.as_mut_ptr(),
+                PEEKED_EVENTS as DWORD,
+                &mut count,
+            )
+        };
+
+        result != 0
+            && records[..count as usize]
+                .iter()
+                .any(|record| record.EventType == WINDOW_BUFFER_SIZE_EVENT)
+    }
+}
@@ -0,0 +1,257 @@
+use std::io::Write;
+use std::panic;
+use std::sync::{Mutex, MutexGuard, Once};
+
+use crossterm_utils::{write_cout, Command, ExecutableCommand, Result};
+use lazy_static::lazy_static;
+
+use crate::alternate::ansi::RESET_SCREEN_STATE_CSI_SEQUENCE;
+use crate::{dumb, raw};
+
+lazy_static! {
+    // Active screen state changes in the order they were made
+    static ref STATE_CHANGES: Mutex<Vec<StateChange>> = Mutex::new(Vec::new());
+}
+
+#[derive(Clone, Copy)]
+struct StateChange {
+    name: &'static str,
+    revert: fn() -> Result<()>,
+}
+
+// Locks the state changes, a poisoned lock is recovered (the changes are always consistent)
+fn lock_changes() -> MutexGuard<'static, Vec<StateChange>> {
+    STATE_CHANGES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+static INSTALL_EXIT_HANDLERS: Once = Once::new();
+
+/// Registers an active screen state change (noop if it's already registered).
+pub(crate) fn register(name: &'static str, revert: fn() -> Result<()>) {
+    let mut changes = lock_changes();
+
+    if !changes.iter().any(|change| change.name == name) {
+        changes.push(StateChange { name, revert });
+    }
+}
+
+/// Unregisters a screen state change which is no longer active.
+pub(crate) fn unregister(name: &'static str) {
+    lock_changes().retain(|change| change.name != name);
+}
+
+/// Restores the screen.
+///
+/// All the active screen state changes made by this crate (raw mode, alternate
+/// screen, ...) are reverted in the reverse order. It works even if the
+/// `RawScreen` or `AlternateScreen` values were leaked (`mem::forget`) and it's
+/// safe to drop them afterwards.
+///
+/// All the changes are reverted even if some of them fail, the first error is returned.
+pub fn restore_all() -> Result<()> {
+    // Do not hold the lock, revert functions do unregister themselves
+    let changes = lock_changes().clone();
+    let mut result = Ok(());
+
+    for change in changes.iter().rev() {
+        if let Err(e) = (change.revert)() {
+            if result.is_ok() {
+                result = Err(e);
+            }
+        }
+    }
+
+    result
+}
+
+/// Resets the screen state unconditionally.
+///
+/// The active screen state changes are reverted first (see the
+/// [`restore_all`](fn.restore_all.html) function). Then the main screen is shown, the
+/// scroll region is reset, the mouse capture, bracketed paste, and focus reporting are
+/// disabled, the line wrap is enabled, and the cursor is shown even if they weren't
+/// changed by this crate. Finally, the raw mode enabled by this crate is disabled (the
+/// raw mode enabled by someone else is left untouched). It's intended for the
+/// `catch_unwind` handlers and the "my terminal is broken" recovery paths.
+///
+/// All the steps are made even if some of them fail, the first error is returned.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::panic;
+/// use crossterm_screen::reset_screen_state;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     if panic::catch_unwind(|| { /* Run the app */ }).is_err() {
+///         reset_screen_state()?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn reset_screen_state() -> Result<()> {
+    let mut result = restore_all();
+
+    for step in [write_reset_sequence, raw::disable_all_raw_modes].iter() {
+        if let Err(e) = step() {
+            if result.is_ok() {
+                result = Err(e);
+            }
+        }
+    }
+
+    result
+}
+
+/// A command to reset the screen state unconditionally.
+///
+/// The command writes the reset sequence only. Use the
+/// [`execute_on`](struct.ResetScreenState.html#method.execute_on) method to do the same as
+/// the [`reset_screen_state`](fn.reset_screen_state.html) function, but with the reset
+/// sequence written to the given writer (the reverted state changes write to the standard
+/// output).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::stderr;
+/// use crossterm_screen::{Result, ResetScreenState};
+///
+/// fn main() -> Result<()> {
+///     ResetScreenState.execute_on(&mut stderr())
+/// }
+/// ```
+pub struct ResetScreenState;
+
+impl Command for ResetScreenState {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(RESET_SCREEN_STATE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        reset_screen_state()
+    }
+}
+
+impl ResetScreenState {
+    /// Reverts the state changes, writes the reset sequence, and disables the raw mode.
+    ///
+    /// All the steps are made even if some of them fail, the first error is returned.
+    pub fn execute_on<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let reverted = restore_all();
+        let written = writer.execute(ResetScreenState).map(|_| ());
+        let disabled = raw::disable_all_raw_modes();
+
+        reverted.and(written).and(disabled)
+    }
+}
+
+fn write_reset_sequence() -> Result<()> {
+    // The legacy console doesn't support the sequences
+    #[cfg(windows)]
+    {
+        if !crossterm_utils::supports_ansi() {
+            return Ok(());
+        }
+    }
+
+    write_cout!(dumb::unless_dumb(RESET_SCREEN_STATE_CSI_SEQUENCE))?;
+    Ok(())
+}
+
+/// Installs a panic hook which restores the screen.
+///
+/// The raw mode is disabled and the main screen is shown before the panic
+/// message is printed, so the message (and the backtrace) isn't rendered into
+/// the alternate screen in the raw mode. The hook is chained onto the existing one.
+///
+/// It's safe to call this function several times, the hook is installed once.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{install_restore_panic_hook, AlternateScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     install_restore_panic_hook();
+///
+///     let _alternate = AlternateScreen::to_alternate(true)?;
+///
+///     panic!("The message is printed on the main screen");
+/// }
+/// ```
+pub fn install_restore_panic_hook() {
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore_all();
+            hook(info);
+        }));
+    });
+}
+
+/// Restores the screen when the process exits.
+///
+/// The [`restore_all`](fn.restore_all.html) function is registered with `atexit`,
+/// so the screen is restored when `std::process::exit` is called. On Windows, it's
+/// called from a console control handler as well (`Ctrl+C`, console window closed, ...).
+///
+/// It's safe to call this function several times, the handlers are installed once.
+pub fn restore_on_exit() -> Result<()> {
+    let mut result = Ok(());
+
+    INSTALL_EXIT_HANDLERS.call_once(|| result = install_exit_handlers());
+
+    result
+}
+
+extern "C" fn restore_at_exit() {
+    let _ = restore_all();
+}
+
+#[cfg(unix)]
+fn install_exit_handlers() -> Result<()> {
+    if unsafe { libc::atexit(restore_at_exit) } != 0 {
+        return Err(std::io::Error::other("Unable to register the atexit handler").into());
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn install_exit_handlers() -> Result<()> {
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE};
+    use winapi::um::consoleapi::SetConsoleCtrlHandler;
+
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> i32;
+    }
+
+    unsafe extern "system" fn restore_on_ctrl(_: DWORD) -> BOOL {
+        let _ = restore_all();
+        // Let the next handler (the default one terminates the process) handle it
+        FALSE
+    }
+
+    if unsafe { atexit(restore_at_exit) } != 0 {
+        return Err(std::io::Error::other("Unable to register the atexit handler").into());
+    }
+
+    if unsafe { SetConsoleCtrlHandler(Some(restore_on_ctrl), 1) } == 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(())
+}
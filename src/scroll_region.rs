@@ -0,0 +1,306 @@
+#[cfg(windows)]
+use std::sync::Mutex;
+
+#[cfg(windows)]
+use crossterm_utils::Result;
+use crossterm_utils::{csi, Command};
+#[cfg(windows)]
+use lazy_static::lazy_static;
+
+use crate::dumb;
+
+#[cfg(windows)]
+lazy_static! {
+    // The scroll region (top, bottom) set by the `SetScrollRegion` command, the whole
+    // window if `None` (legacy Windows)
+    static ref SCROLL_REGION: Mutex<Option<(u16, u16)>> = Mutex::new(None);
+}
+
+/// A command to set the scroll region (`DECSTBM`).
+///
+/// The first value is the top row and the second value is the bottom row (both
+/// inclusive, 0-based). Only the lines of the region are scrolled, the lines outside of
+/// it stay (like a fixed header and footer). The cursor is moved to the origin.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The legacy Windows console (no ANSI support) doesn't scroll the region when the
+/// text is written. Use the [`ScrollRegionUp`](struct.ScrollRegionUp.html) and
+/// [`ScrollRegionDown`](struct.ScrollRegionDown.html) commands to scroll it
+/// (`ScrollConsoleScreenBuffer` clipped to the region).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, ResetScrollRegion, ScrollRegionUp, SetScrollRegion};
+///
+/// fn main() -> Result<()> {
+///     // Fixed header (row 0) and footer (row 23)
+///     execute!(stdout(), SetScrollRegion(1, 22))?;
+///
+///     execute!(stdout(), ScrollRegionUp(1))?;
+///
+///     execute!(stdout(), ResetScrollRegion)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetScrollRegion(pub u16, pub u16);
+
+impl Command for SetScrollRegion {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(format!(csi!("{};{}r"), self.0 + 1, self.1 + 1))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        *lock_scroll_region() = Some((self.0, self.1));
+        legacy::move_to_origin()
+    }
+}
+
+/// A command to reset the scroll region to the whole screen.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct ResetScrollRegion;
+
+impl Command for ResetScrollRegion {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("r"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        lock_scroll_region().take();
+        legacy::move_to_origin()
+    }
+}
+
+/// A command to scroll the lines of the scroll region up by the given number of lines.
+///
+/// New blank lines are added at the bottom of the region.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollRegionUp(pub u16);
+
+impl Command for ScrollRegionUp {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(format!(csi!("{}S"), self.0))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::scroll(*lock_scroll_region(), -(self.0 as i16))
+    }
+}
+
+/// A command to scroll the lines of the scroll region down by the given number of lines.
+///
+/// New blank lines are added at the top of the region.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollRegionDown(pub u16);
+
+impl Command for ScrollRegionDown {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(format!(csi!("{}T"), self.0))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::scroll(*lock_scroll_region(), self.0 as i16)
+    }
+}
+
+/// A command to enable the left & right margins mode (`DECLRMM`).
+///
+/// The margins set by the [`SetLeftRightMargins`](struct.SetLeftRightMargins.html)
+/// command are used only when this mode is enabled.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the support ignore it. It's a noop on the legacy Windows console
+/// (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{
+///     execute, Result, DisableLeftRightMargins, EnableLeftRightMargins, SetLeftRightMargins,
+///     SetScrollRegion,
+/// };
+///
+/// fn main() -> Result<()> {
+///     // Pane pinned to the columns 40 - 79 & the rows 1 - 22
+///     execute!(
+///         stdout(),
+///         EnableLeftRightMargins,
+///         SetLeftRightMargins(40, 79),
+///         SetScrollRegion(1, 22)
+///     )?;
+///
+///     // Render the pane
+///
+///     execute!(stdout(), DisableLeftRightMargins)
+/// }
+/// ```
+pub struct EnableLeftRightMargins;
+
+impl Command for EnableLeftRightMargins {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?69h"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to disable the left & right margins mode (`DECLRMM`).
+///
+/// The whole line width is used again.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct DisableLeftRightMargins;
+
+impl Command for DisableLeftRightMargins {
+    type AnsiType = &'static str;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(csi!("?69l"))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to set the left & right margins (`DECSLRM`).
+///
+/// The first value is the left column and the second value is the right column (both
+/// inclusive, 0-based). Together with the scroll region, only the text inside of the
+/// margins is scrolled. The cursor is moved to the origin.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// The margins are ignored unless the
+/// [`EnableLeftRightMargins`](struct.EnableLeftRightMargins.html) command is executed
+/// first. It's a noop on the legacy Windows console (no ANSI support).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetLeftRightMargins(pub u16, pub u16);
+
+impl Command for SetLeftRightMargins {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(format!(csi!("{};{}s"), self.0 + 1, self.1 + 1))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+fn lock_scroll_region() -> std::sync::MutexGuard<'static, Option<(u16, u16)>> {
+    SCROLL_REGION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::mem;
+
+    use crossterm_utils::Result;
+    use crossterm_winapi::Handle;
+    use winapi::um::wincon::{
+        GetConsoleScreenBufferInfo, ScrollConsoleScreenBufferW, SetConsoleCursorPosition,
+        CHAR_INFO, CONSOLE_SCREEN_BUFFER_INFO, COORD, SMALL_RECT,
+    };
+    use winapi::um::winnt::HANDLE;
+
+    use crate::alternate::windows::alternate_screen_buffer;
+    use crate::sys::winapi::result;
+
+    // Moves the cursor to the origin of the visible window
+    pub(super) fn move_to_origin() -> Result<()> {
+        let (handle, info) = screen_buffer()?;
+        let origin = COORD {
+            X: info.srWindow.Left,
+            Y: info.srWindow.Top,
+        };
+
+        result(unsafe { SetConsoleCursorPosition(handle, origin) })
+    }
+
+    // Scrolls the region (the whole window if `None`) by the given number of lines,
+    // negative scrolls up
+    pub(super) fn scroll(region: Option<(u16, u16)>, lines: i16) -> Result<()> {
+        let (handle, info) = screen_buffer()?;
+        let window = info.srWindow;
+
+        let rect = match region {
+            Some((top, bottom)) => SMALL_RECT {
+                Left: window.Left,
+                Top: window.Top + top as i16,
+                Right: window.Right,
+                Bottom: (window.Top + bottom as i16).min(window.Bottom),
+            },
+            None => window,
+        };
+
+        let destination = COORD {
+            X: rect.Left,
+            Y: rect.Top + lines,
+        };
+
+        let mut fill: CHAR_INFO = unsafe { mem::zeroed() };
+        unsafe { *fill.Char.UnicodeChar_mut() = ' ' as u16 };
+        fill.Attributes = info.wAttributes;
+
+        // The region is the clip rectangle as well, the lines outside of it are untouched
+        result(unsafe { ScrollConsoleScreenBufferW(handle, &rect, &rect, destination, &fill) })
+    }
+
+    // Returns the active screen buffer (the alternate one if it's active) and its info
+    fn screen_buffer() -> Result<(HANDLE, CONSOLE_SCREEN_BUFFER_INFO)> {
+        let handle = match alternate_screen_buffer() {
+            Some(handle) => handle,
+            None => Handle::output_handle()?,
+        };
+
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleScreenBufferInfo(handle, &mut info) })?;
+
+        Ok((handle, info))
+    }
+}
@@ -0,0 +1,233 @@
+use crossterm_utils::{Command, Result};
+
+use crate::alternate::{ansi, Features, Output};
+use crate::{
+    dumb, focus, install_restore_panic_hook, restore_on_exit, AlternateScreen, DisableFocusChange,
+    EnableFocusChange, RawScreen,
+};
+
+/// A guard of the whole terminal setup of a TUI.
+///
+/// The raw mode, the alternate screen, the cursor, the mouse capture, the bracketed
+/// paste, the focus change reporting, the panic hook, and the exit restoration are
+/// bundled behind a single builder (see the [`builder`](#method.builder) method).
+/// Everything is reverted in the reverse order when you drop the `TerminalSession`
+/// value. The restoration (see the [`restore_all`](fn.restore_all.html) function)
+/// kicks in on a panic and on `std::process::exit` as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::TerminalSession;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let _session = TerminalSession::builder()
+///         .raw_mode(true)
+///         .hide_cursor(true)
+///         .mouse_capture(true)
+///         .bracketed_paste(true)
+///         .focus_change(true)
+///         .start()?;
+///
+///     // Run the TUI
+///
+///     Ok(())
+/// } // `_session` dropped here <- everything reverted & back to main screen
+/// ```
+pub struct TerminalSession {
+    focus_change: bool,
+    // The features enabled on the main screen (without the alternate screen)
+    features: Option<Features>,
+    // The raw mode enabled on the main screen (without the alternate screen), it's
+    // disabled when the value is dropped
+    _raw_screen: Option<RawScreen>,
+    alternate: Option<AlternateScreen>,
+}
+
+impl TerminalSession {
+    /// Returns a builder of the terminal session.
+    ///
+    /// See the [`TerminalSessionBuilder`](struct.TerminalSessionBuilder.html)
+    /// documentation for the defaults.
+    pub fn builder() -> TerminalSessionBuilder {
+        TerminalSessionBuilder {
+            alternate_screen: true,
+            raw_mode: false,
+            features: Features::default(),
+            focus_change: false,
+            panic_hook: true,
+            restore_on_exit: true,
+        }
+    }
+
+    /// Returns the alternate screen, `None` if the session runs on the main screen.
+    pub fn alternate_screen(&self) -> Option<&AlternateScreen> {
+        self.alternate.as_ref()
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        if self.focus_change && write_sequence(DisableFocusChange.ansi_code()).is_ok() {
+            focus::untrack();
+        }
+
+        if let Some(features) = self.features {
+            let _ = write_sequence(&features.disable_sequence());
+        }
+
+        // The raw mode & the alternate screen are reverted when the fields are dropped
+    }
+}
+
+/// A builder of the [`TerminalSession`](struct.TerminalSession.html).
+///
+/// The alternate screen, the panic hook, and the exit restoration are enabled by
+/// default, everything else is disabled.
+///
+/// The cursor, the mouse capture, the bracketed paste, and the focus change options are
+/// ignored by the legacy Windows console (no ANSI support).
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalSessionBuilder {
+    alternate_screen: bool,
+    raw_mode: bool,
+    features: Features,
+    focus_change: bool,
+    panic_hook: bool,
+    restore_on_exit: bool,
+}
+
+impl TerminalSessionBuilder {
+    /// Switches to the alternate screen (enabled by default).
+    ///
+    /// The session runs on the main screen otherwise (like an inline TUI).
+    pub fn alternate_screen(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.alternate_screen = enabled;
+        self
+    }
+
+    /// Enables the raw mode (disabled by default).
+    pub fn raw_mode(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.raw_mode = enabled;
+        self
+    }
+
+    /// Hides the cursor (disabled by default).
+    pub fn hide_cursor(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.features.hide_cursor = enabled;
+        self
+    }
+
+    /// Enables the mouse capture (disabled by default).
+    pub fn mouse_capture(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.features.mouse_capture = enabled;
+        self
+    }
+
+    /// Enables the bracketed paste (disabled by default).
+    pub fn bracketed_paste(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.features.bracketed_paste = enabled;
+        self
+    }
+
+    /// Enables the focus change reporting (disabled by default).
+    ///
+    /// See the [`EnableFocusChange`](struct.EnableFocusChange.html) documentation.
+    pub fn focus_change(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.focus_change = enabled;
+        self
+    }
+
+    /// Installs the panic hook restoring the screen (enabled by default).
+    ///
+    /// See the [`install_restore_panic_hook`](fn.install_restore_panic_hook.html)
+    /// function.
+    pub fn panic_hook(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.panic_hook = enabled;
+        self
+    }
+
+    /// Restores the screen when the process exits (enabled by default).
+    ///
+    /// See the [`restore_on_exit`](fn.restore_on_exit.html) function.
+    pub fn restore_on_exit(mut self, enabled: bool) -> TerminalSessionBuilder {
+        self.restore_on_exit = enabled;
+        self
+    }
+
+    /// Starts the terminal session.
+    ///
+    /// Everything enabled so far is reverted if this function fails.
+    pub fn start(self) -> Result<TerminalSession> {
+        if self.panic_hook {
+            install_restore_panic_hook();
+        }
+
+        if self.restore_on_exit {
+            restore_on_exit()?;
+        }
+
+        let mut session = if self.alternate_screen {
+            let alternate = AlternateScreen::builder()
+                .raw_mode(self.raw_mode)
+                .hide_cursor(self.features.hide_cursor)
+                .mouse_capture(self.features.mouse_capture)
+                .bracketed_paste(self.features.bracketed_paste)
+                .enter()?;
+
+            TerminalSession {
+                focus_change: false,
+                features: None,
+                _raw_screen: None,
+                alternate: Some(alternate),
+            }
+        } else {
+            let raw_screen = if self.raw_mode {
+                Some(RawScreen::into_raw_mode()?)
+            } else {
+                None
+            };
+
+            let mut session = TerminalSession {
+                focus_change: false,
+                features: None,
+                _raw_screen: raw_screen,
+                alternate: None,
+            };
+
+            write_sequence(&self.features.enable_sequence())?;
+            session.features = Some(self.features);
+            session
+        };
+
+        if self.focus_change {
+            write_sequence(EnableFocusChange.ansi_code())?;
+            session.focus_change = true;
+
+            // Keep it last - track the reporting only if we were able to enable it
+            if !dumb::suppressed() {
+                focus::track();
+            }
+        }
+
+        Ok(session)
+    }
+}
+
+// Writes the sequence to the standard output, the legacy Windows console doesn't
+// support the sequences
+fn write_sequence(sequence: &str) -> Result<()> {
+    #[cfg(windows)]
+    {
+        if !crossterm_utils::supports_ansi() {
+            return Ok(());
+        }
+    }
+
+    if !sequence.is_empty() {
+        ansi::write_sequence(Output::Stdout, sequence)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+#[cfg(unix)]
+use std::time::Duration;
+
+#[cfg(unix)]
+use crossterm_utils::csi;
+use crossterm_utils::Result;
+
+#[cfg(unix)]
+use crate::{sys, RawScreen};
+
+// How long to wait for the terminal response
+#[cfg(unix)]
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Returns the terminal size `(columns, rows)`.
+///
+/// The size of the terminal the process is attached to (`TIOCGWINSZ`) on UNIX and the
+/// size of the visible window of the console screen buffer
+/// (`GetConsoleScreenBufferInfo`) on Windows.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let (columns, rows) = crossterm_screen::size()?;
+///     println!("{}x{}", columns, rows);
+///
+///     Ok(())
+/// }
+/// ```
+#[cfg(unix)]
+pub fn size() -> Result<(u16, u16)> {
+    let size = sys::RawModeCommand::new()?.window_size()?;
+    Ok((size.ws_col, size.ws_row))
+}
+
+/// Returns the terminal size `(columns, rows)`.
+///
+/// The size of the terminal the process is attached to (`TIOCGWINSZ`) on UNIX and the
+/// size of the visible window of the console screen buffer
+/// (`GetConsoleScreenBufferInfo`) on Windows.
+#[cfg(windows)]
+pub fn size() -> Result<(u16, u16)> {
+    let info = crate::alternate::windows::main_screen_buffer_info()?;
+    let window = info.srWindow;
+
+    Ok((
+        (window.Right - window.Left + 1) as u16,
+        (window.Bottom - window.Top + 1) as u16,
+    ))
+}
+
+/// Returns the terminal size in pixels `(width, height)`.
+///
+/// The pixel size reported by the kernel (`TIOCGWINSZ`) is used if it's known, the
+/// terminal is asked (`XTWINOPS 14`) otherwise. The raw mode is enabled for the time of
+/// the query and the response is awaited for up to 100ms. `None` is returned if the
+/// size is unknown.
+///
+/// UNIX only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     if let Some((width, height)) = crossterm_screen::size_pixels()? {
+///         println!("{}x{} pixels", width, height);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[cfg(unix)]
+pub fn size_pixels() -> Result<Option<(u16, u16)>> {
+    let size = sys::RawModeCommand::new()?.window_size()?;
+
+    if size.ws_xpixel != 0 && size.ws_ypixel != 0 {
+        return Ok(Some((size.ws_xpixel, size.ws_ypixel)));
+    }
+
+    let raw = RawScreen::into_raw_mode()?;
+    let response = raw.command().query(csi!("14t"), b't', RESPONSE_TIMEOUT)?;
+
+    Ok(response.and_then(|response| parse_pixel_size_report(&response)))
+}
+
+// Parses the pixel size response (`CSI 4 ; <height> ; <width> t`), `(width, height)`
+#[cfg(unix)]
+fn parse_pixel_size_report(response: &[u8]) -> Option<(u16, u16)> {
+    let response = String::from_utf8_lossy(response);
+    let prefix = "\x1B[4;";
+
+    let start = response.find(prefix)? + prefix.len();
+    let mut values = response[start..].trim_end_matches('t').split(';');
+
+    let height = values.next()?.parse().ok()?;
+    let width = values.next()?.parse().ok()?;
+
+    Some((width, height))
+}
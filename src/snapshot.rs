@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use lazy_static::lazy_static;
+
+use crate::buffer::CONTINUATION;
+
+lazy_static! {
+    // The last frame written by a `VirtualScreen` capturing its frames (the columns & the
+    // cells row by row), the shadow of the alternate screen (terminals can't be asked for
+    // their contents)
+    static ref SHADOW: Mutex<Option<(u16, Arc<Vec<char>>)>> = Mutex::new(None);
+}
+
+// Locks the shadow frame, a poisoned lock is recovered (the frame is always consistent)
+fn lock_shadow() -> MutexGuard<'static, Option<(u16, Arc<Vec<char>>)>> {
+    SHADOW.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Remembers the frame written by a `VirtualScreen` capturing its frames
+pub(crate) fn record_frame(columns: u16, cells: Arc<Vec<char>>) {
+    *lock_shadow() = Some((columns, cells));
+}
+
+// Forgets the frame, the alternate screen is entered (it's empty)
+pub(crate) fn clear_shadow() {
+    lock_shadow().take();
+}
+
+/// A snapshot of the visible screen contents (text only).
+///
+/// It allows a TUI to start by showing the pre-existing screen on the alternate screen
+/// and to take over smoothly. Use the
+/// [`AlternateScreen::replay`](struct.AlternateScreen.html#method.replay) method to
+/// write it to the alternate screen.
+///
+/// Terminals can't be asked for their contents. The snapshot is captured from the
+/// console on Windows ([`capture`](#method.capture)) and it must be provided by the
+/// caller on UNIX ([`from_lines`](#method.from_lines)).
+///
+/// The alternate screen can be captured as well, see the
+/// [`AlternateScreen::capture`](struct.AlternateScreen.html#method.capture) method.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{AlternateScreen, ScreenSnapshot};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let snapshot = ScreenSnapshot::from_lines(vec!["$ cargo run", "Compiling..."]);
+///
+///     let alternate = AlternateScreen::to_alternate(true)?;
+///     alternate.replay(&snapshot)?;
+///
+///     // Take over the screen
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenSnapshot {
+    lines: Vec<String>,
+}
+
+impl ScreenSnapshot {
+    /// Creates a snapshot from the given lines (top to bottom).
+    pub fn from_lines<I, S>(lines: I) -> ScreenSnapshot
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ScreenSnapshot {
+            lines: lines.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Captures the visible window of the main screen buffer (`ReadConsoleOutput`).
+    ///
+    /// Call it before the alternate screen is entered. The trailing spaces of every line
+    /// are trimmed.
+    ///
+    /// Windows only.
+    #[cfg(windows)]
+    pub fn capture() -> crossterm_utils::Result<ScreenSnapshot> {
+        Ok(ScreenSnapshot {
+            lines: crate::alternate::windows::read_main_screen_lines()?,
+        })
+    }
+
+    // Creates a snapshot of the last captured frame, the trailing
+    // spaces of every line are trimmed
+    pub(crate) fn from_shadow() -> ScreenSnapshot {
+        let lines = match &*lock_shadow() {
+            Some((columns, cells)) if *columns > 0 => cells
+                .chunks(*columns as usize)
+                .map(|row| {
+                    row.iter()
+                        .filter(|character| **character != CONTINUATION)
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string()
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        ScreenSnapshot { lines }
+    }
+
+    /// Returns the lines of the snapshot (top to bottom).
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    // Returns the text to write to the screen, from the origin
+    pub(crate) fn text(&self) -> String {
+        self.lines.join("\r\n")
+    }
+}
@@ -0,0 +1,137 @@
+use crossterm_utils::{csi, Command, Result};
+
+use crate::alternate::{ansi, Output};
+use crate::{dumb, Passthrough};
+
+// The mode 2026 is followed by the older DCS-based protocol, terminals ignore the one
+// they don't support
+static BEGIN_SYNCHRONIZED_UPDATE_SEQUENCE: &str = concat!(csi!("?2026h"), "\x1BP=1s\x1B\\");
+static END_SYNCHRONIZED_UPDATE_SEQUENCE: &str = concat!(csi!("?2026l"), "\x1BP=2s\x1B\\");
+
+/// A command to begin the synchronized update (`?2026h`).
+///
+/// The terminal keeps showing the last frame until the
+/// [`EndSynchronizedUpdate`](struct.EndSynchronizedUpdate.html) command is executed. It
+/// eliminates the tearing & flickering of frame-based renderers. The older DCS-based
+/// protocol is written as well. Both are wrapped in the terminal multiplexer envelope,
+/// see [`Passthrough`](enum.Passthrough.html).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the support ignore it. It's a noop on the legacy Windows console
+/// (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, BeginSynchronizedUpdate, EndSynchronizedUpdate};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), BeginSynchronizedUpdate)?;
+///
+///     // Render the frame
+///
+///     execute!(stdout(), EndSynchronizedUpdate)
+/// }
+/// ```
+pub struct BeginSynchronizedUpdate;
+
+impl Command for BeginSynchronizedUpdate {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(Passthrough::current().wrap(BEGIN_SYNCHRONIZED_UPDATE_SEQUENCE))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A command to end the synchronized update (`?2026l`).
+///
+/// The terminal shows everything written since the
+/// [`BeginSynchronizedUpdate`](struct.BeginSynchronizedUpdate.html) command at once.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+pub struct EndSynchronizedUpdate;
+
+impl Command for EndSynchronizedUpdate {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        dumb::unless_dumb(Passthrough::current().wrap(END_SYNCHRONIZED_UPDATE_SEQUENCE))
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A guard of the synchronized update.
+///
+/// The synchronized update begins when the guard is created and it ends when you drop
+/// the `SynchronizedUpdate` value. The sequences are written to the standard output.
+/// See the [`BeginSynchronizedUpdate`](struct.BeginSynchronizedUpdate.html) command.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::SynchronizedUpdate;
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     {
+///         let _update = SynchronizedUpdate::begin()?;
+///         write!(stdout(), "Frame")?;
+///     } // `_update` dropped here <- the frame is shown
+///
+///     Ok(())
+/// }
+/// ```
+pub struct SynchronizedUpdate {
+    _private: (),
+}
+
+impl SynchronizedUpdate {
+    /// Begins the synchronized update.
+    pub fn begin() -> Result<SynchronizedUpdate> {
+        write_sequence(BEGIN_SYNCHRONIZED_UPDATE_SEQUENCE)?;
+        Ok(SynchronizedUpdate { _private: () })
+    }
+}
+
+impl Drop for SynchronizedUpdate {
+    fn drop(&mut self) {
+        let _ = write_sequence(END_SYNCHRONIZED_UPDATE_SEQUENCE);
+    }
+}
+
+#[cfg(windows)]
+fn write_sequence(sequence: &str) -> Result<()> {
+    // The legacy console doesn't support the sequences
+    if crossterm_utils::supports_ansi() {
+        ansi::write_sequence(
+            Output::Stdout,
+            &dumb::unless_dumb(Passthrough::current().wrap(sequence)),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_sequence(sequence: &str) -> Result<()> {
+    ansi::write_sequence(
+        Output::Stdout,
+        &dumb::unless_dumb(Passthrough::current().wrap(sequence)),
+    )?;
+    Ok(())
+}
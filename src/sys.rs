@@ -1,5 +1,37 @@
 #[cfg(unix)]
+pub(crate) use self::unix::{attribute_flags, is_std_tty, RawModeCommand};
+#[cfg(windows)]
+pub(crate) use self::winapi::{attribute_flags, is_std_tty, RawModeCommand};
+#[cfg(all(windows, feature = "recording"))]
+pub use self::{
+    fake_console::FakeConsole,
+    winapi::{set_console_backend, ConsoleBackend},
+};
+
+// Redox OS is a UNIX target, the raw mode & the alternate screen are handled by the `unix`
+// module, `redox` only holds the differences
+#[cfg(target_os = "redox")]
+mod redox;
+#[cfg(unix)]
 pub(crate) mod unix;
 
+#[cfg(all(windows, feature = "recording"))]
+mod fake_console;
+#[cfg(windows)]
+pub(crate) mod msys;
 #[cfg(windows)]
 pub(crate) mod winapi;
+
+/// Identifies the terminal the raw mode is applied to.
+///
+/// It's a file descriptor on UNIX and an address of the console input handle on Windows.
+#[cfg(unix)]
+pub(crate) type Tty = std::os::unix::io::RawFd;
+#[cfg(windows)]
+pub(crate) type Tty = usize;
+
+/// Terminal attributes, it's the `termios` structure on UNIX and the console mode on Windows.
+#[cfg(unix)]
+pub(crate) type Attributes = libc::termios;
+#[cfg(windows)]
+pub(crate) type Attributes = winapi::shared::minwindef::DWORD;
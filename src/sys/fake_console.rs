@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::mem;
+use std::os::windows::io::RawHandle;
+use std::sync::{Mutex, MutexGuard};
+
+use crossterm_utils::Result;
+use winapi::shared::minwindef::DWORD;
+use winapi::um::wincon::{
+    CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_ECHO_INPUT, ENABLE_EXTENDED_FLAGS,
+    ENABLE_INSERT_MODE, ENABLE_LINE_INPUT, ENABLE_MOUSE_INPUT, ENABLE_PROCESSED_INPUT,
+    ENABLE_PROCESSED_OUTPUT, ENABLE_QUICK_EDIT_MODE, ENABLE_WRAP_AT_EOL_OUTPUT, SMALL_RECT,
+};
+
+use super::winapi::ConsoleBackend;
+use crate::ScreenError;
+
+// The handles of the fake console, the created screen buffers follow
+const INPUT_HANDLE: usize = 1;
+const OUTPUT_HANDLE: usize = 2;
+
+// The console input mode of a new console
+const DEFAULT_INPUT_MODE: DWORD = ENABLE_PROCESSED_INPUT
+    | ENABLE_LINE_INPUT
+    | ENABLE_ECHO_INPUT
+    | ENABLE_MOUSE_INPUT
+    | ENABLE_INSERT_MODE
+    | ENABLE_QUICK_EDIT_MODE
+    | ENABLE_EXTENDED_FLAGS;
+
+// The console output mode of a new console
+const DEFAULT_OUTPUT_MODE: DWORD = ENABLE_PROCESSED_OUTPUT | ENABLE_WRAP_AT_EOL_OUTPUT;
+
+/// A fake console keeping the console modes & the screen buffers in memory.
+///
+/// Install it with the [`set_console_backend`](fn.set_console_backend.html) function and
+/// the raw mode & the legacy alternate screen (the console screen buffers) can be tested
+/// without a console (like on a CI machine). The fake console has an input handle and a
+/// main screen buffer (`120x30` window, `120x9001` buffer) in the default modes of a new
+/// console.
+///
+/// Windows only, available with the `recording` feature only.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use crossterm_screen::{set_console_backend, FakeConsole, RawScreen};
+/// use crossterm_utils::Result;
+///
+/// fn main() -> Result<()> {
+///     let console = Arc::new(FakeConsole::new());
+///     set_console_backend(Some(console.clone()));
+///
+///     let raw = RawScreen::into_raw_mode()?;
+///     assert_eq!(console.input_mode() & 0x0002, 0); // ENABLE_LINE_INPUT
+///     drop(raw);
+///
+///     set_console_backend(None);
+///     Ok(())
+/// }
+/// ```
+pub struct FakeConsole {
+    state: Mutex<FakeConsoleState>,
+}
+
+struct FakeConsoleState {
+    // The console modes of the handles
+    modes: HashMap<usize, DWORD>,
+    // The screen buffers (the main one included)
+    screen_buffers: HashMap<usize, CONSOLE_SCREEN_BUFFER_INFO>,
+    active_screen_buffer: usize,
+    next_handle: usize,
+    // The number of the input buffer flushes
+    input_flushes: usize,
+}
+
+impl FakeConsole {
+    /// Creates a fake console in the default modes, the main screen buffer is active.
+    pub fn new() -> FakeConsole {
+        let mut modes = HashMap::new();
+        modes.insert(INPUT_HANDLE, DEFAULT_INPUT_MODE);
+        modes.insert(OUTPUT_HANDLE, DEFAULT_OUTPUT_MODE);
+
+        let mut screen_buffers = HashMap::new();
+        screen_buffers.insert(OUTPUT_HANDLE, screen_buffer_info(120, 9001, 120, 30));
+
+        FakeConsole {
+            state: Mutex::new(FakeConsoleState {
+                modes,
+                screen_buffers,
+                active_screen_buffer: OUTPUT_HANDLE,
+                next_handle: OUTPUT_HANDLE + 1,
+                input_flushes: 0,
+            }),
+        }
+    }
+
+    /// Returns the console input mode.
+    pub fn input_mode(&self) -> DWORD {
+        self.lock().modes[&INPUT_HANDLE]
+    }
+
+    /// Sets the console input mode (like the user changed it).
+    pub fn set_input_mode(&self, mode: DWORD) {
+        self.lock().modes.insert(INPUT_HANDLE, mode);
+    }
+
+    /// Returns the console output mode of the main screen buffer.
+    pub fn output_mode(&self) -> DWORD {
+        self.lock().modes[&OUTPUT_HANDLE]
+    }
+
+    /// Returns the number of times the pending input was discarded.
+    pub fn input_flushes(&self) -> usize {
+        self.lock().input_flushes
+    }
+
+    /// Returns the number of the screen buffers, the main one included.
+    pub fn screen_buffers(&self) -> usize {
+        self.lock().screen_buffers.len()
+    }
+
+    /// Checks whether the main screen buffer is the active one.
+    pub fn is_main_screen_buffer_active(&self) -> bool {
+        self.lock().active_screen_buffer == OUTPUT_HANDLE
+    }
+
+    /// Returns the size `(columns, rows)` of the active screen buffer.
+    pub fn active_screen_buffer_size(&self) -> (u16, u16) {
+        let state = self.lock();
+        let info = &state.screen_buffers[&state.active_screen_buffer];
+        (info.dwSize.X as u16, info.dwSize.Y as u16)
+    }
+
+    fn lock(&self) -> MutexGuard<FakeConsoleState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for FakeConsole {
+    fn default() -> FakeConsole {
+        FakeConsole::new()
+    }
+}
+
+impl ConsoleBackend for FakeConsole {
+    fn input_handle(&self) -> Result<RawHandle> {
+        Ok(INPUT_HANDLE as RawHandle)
+    }
+
+    fn output_handle(&self) -> Result<RawHandle> {
+        Ok(OUTPUT_HANDLE as RawHandle)
+    }
+
+    fn mode(&self, handle: RawHandle) -> Result<DWORD> {
+        match self.lock().modes.get(&(handle as usize)) {
+            Some(mode) => Ok(*mode),
+            None => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+
+    fn set_mode(&self, handle: RawHandle, mode: DWORD) -> Result<()> {
+        match self.lock().modes.get_mut(&(handle as usize)) {
+            Some(current) => {
+                *current = mode;
+                Ok(())
+            }
+            None => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+
+    fn flush_input(&self, handle: RawHandle) -> Result<()> {
+        if handle as usize != INPUT_HANDLE {
+            return Err(ScreenError::NotATerminal.into());
+        }
+
+        self.lock().input_flushes += 1;
+        Ok(())
+    }
+
+    fn create_screen_buffer(&self) -> Result<RawHandle> {
+        let mut state = self.lock();
+
+        let handle = state.next_handle;
+        state.next_handle += 1;
+        state.modes.insert(handle, DEFAULT_OUTPUT_MODE);
+        // A new screen buffer has the size of the window
+        let window = state.screen_buffers[&state.active_screen_buffer].srWindow;
+        let (columns, rows) = (
+            window.Right - window.Left + 1,
+            window.Bottom - window.Top + 1,
+        );
+        state
+            .screen_buffers
+            .insert(handle, screen_buffer_info(columns, rows, columns, rows));
+
+        Ok(handle as RawHandle)
+    }
+
+    fn show_screen_buffer(&self, handle: RawHandle) -> Result<()> {
+        let mut state = self.lock();
+
+        if !state.screen_buffers.contains_key(&(handle as usize)) {
+            return Err(ScreenError::NotATerminal.into());
+        }
+
+        state.active_screen_buffer = handle as usize;
+        Ok(())
+    }
+
+    fn screen_buffer_info(&self, handle: RawHandle) -> Result<CONSOLE_SCREEN_BUFFER_INFO> {
+        match self.lock().screen_buffers.get(&(handle as usize)) {
+            Some(info) => Ok(*info),
+            None => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+
+    fn set_screen_buffer_size(&self, handle: RawHandle, size: COORD) -> Result<()> {
+        match self.lock().screen_buffers.get_mut(&(handle as usize)) {
+            Some(info) => {
+                info.dwSize = size;
+                Ok(())
+            }
+            None => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+
+    fn restore_screen_buffer(
+        &self,
+        handle: RawHandle,
+        saved: &CONSOLE_SCREEN_BUFFER_INFO,
+    ) -> Result<()> {
+        match self.lock().screen_buffers.get_mut(&(handle as usize)) {
+            Some(info) => {
+                info.wAttributes = saved.wAttributes;
+                info.srWindow = saved.srWindow;
+                info.dwCursorPosition = saved.dwCursorPosition;
+                Ok(())
+            }
+            None => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+}
+
+// Returns the info of a screen buffer with the window at the top left corner
+fn screen_buffer_info(
+    columns: i16,
+    rows: i16,
+    window_columns: i16,
+    window_rows: i16,
+) -> CONSOLE_SCREEN_BUFFER_INFO {
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+    info.dwSize = COORD {
+        X: columns,
+        Y: rows,
+    };
+    info.wAttributes = 0x07;
+    info.srWindow = SMALL_RECT {
+        Left: 0,
+        Top: 0,
+        Right: window_columns - 1,
+        Bottom: window_rows - 1,
+    };
+    info.dwMaximumWindowSize = COORD {
+        X: window_columns,
+        Y: window_rows,
+    };
+    info
+}
@@ -0,0 +1,90 @@
+use std::process::{Command, Stdio};
+use std::{mem, slice};
+
+use crossterm_utils::Result;
+use winapi::ctypes::c_void;
+use winapi::shared::minwindef::MAX_PATH;
+use winapi::um::fileapi::FILE_NAME_INFO;
+use winapi::um::minwinbase::FileNameInfo;
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::winbase::{GetFileInformationByHandleEx, STD_OUTPUT_HANDLE};
+use winapi::um::winnt::HANDLE;
+
+use crate::{ConsoleInputModeFlags, ScreenError};
+
+// Checks whether the handle is a Cygwin/MSYS pseudo terminal (like Mintty)
+//
+// The pseudo terminal is a named pipe (`\msys-1888ae32e00d56aa-pty0-from-master`), it's
+// not a console and `GetConsoleMode` fails. Both the `msys-`/`cygwin-` and the `-pty`
+// parts of the pipe name are required, a regular file named `pty` doesn't match.
+pub(crate) fn is_msys_pty(handle: HANDLE) -> bool {
+    // `u32`s keep the buffer aligned for the `FILE_NAME_INFO` structure
+    let size = mem::size_of::<FILE_NAME_INFO>() + MAX_PATH * mem::size_of::<u16>();
+    let mut buffer = vec![0u32; size / mem::size_of::<u32>() + 1];
+
+    let result = unsafe {
+        GetFileInformationByHandleEx(
+            handle,
+            FileNameInfo,
+            buffer.as_mut_ptr() as *mut c_void,
+            (buffer.len() * mem::size_of::<u32>()) as u32,
+        )
+    };
+
+    if result == 0 {
+        return false;
+    }
+
+    let info = unsafe { &*(buffer.as_ptr() as *const FILE_NAME_INFO) };
+    let name = unsafe {
+        slice::from_raw_parts(
+            info.FileName.as_ptr(),
+            info.FileNameLength as usize / mem::size_of::<u16>(),
+        )
+    };
+    let name = String::from_utf16_lossy(name);
+
+    (name.contains("msys-") || name.contains("cygwin-")) && name.contains("-pty")
+}
+
+// Checks whether the standard output is a Cygwin/MSYS pseudo terminal
+pub(crate) fn is_msys_output() -> bool {
+    is_msys_pty(unsafe { GetStdHandle(STD_OUTPUT_HANDLE) })
+}
+
+// Returns the `stty` arguments clearing the flags of the mask
+pub(crate) fn raw_mode_arguments(mask: ConsoleInputModeFlags) -> Vec<&'static str> {
+    let mut arguments = Vec::new();
+
+    if mask.contains(ConsoleInputModeFlags::LINE_INPUT) {
+        arguments.extend(&["-icanon", "min", "1", "time", "0"]);
+    }
+
+    if mask.contains(ConsoleInputModeFlags::ECHO_INPUT) {
+        arguments.push("-echo");
+    }
+
+    if mask.contains(ConsoleInputModeFlags::PROCESSED_INPUT) {
+        arguments.extend(&["-isig", "-iexten", "-ixon"]);
+    }
+
+    arguments
+}
+
+// Runs `stty` of the Cygwin/MSYS installation on the pseudo terminal of the standard
+// input, the native processes can't change the Cygwin tty attributes directly
+//
+// Returns the output of `stty` (like the settings printed by `stty -g`).
+pub(crate) fn stty(arguments: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(arguments)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ScreenError::NotATerminal.into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,18 @@
+// Redox OS specifics of the UNIX backend.
+//
+// There's no separate Redox backend. Redox is a UNIX target and `relibc` implements the
+// `termios` interface (`tcgetattr`, `tcsetattr`, `cfmakeraw`, `TIOCGWINSZ`, ...) on top
+// of the `termios:` & `winsize:` schemes, so the `RawModeCommand` (raw & cbreak modes)
+// and the ANSI alternate screen of `sys::unix` are used as they are. Only the differences
+// live here.
+
+use std::env;
+use std::path::PathBuf;
+
+// Returns the path of the controlling terminal, there's no `/dev/tty` on Redox and the
+// terminal the process is attached to is exposed in the `TTY` environment variable
+pub(crate) fn controlling_terminal_path() -> PathBuf {
+    env::var_os("TTY")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/dev/tty"))
+}
@@ -1,22 +1,639 @@
-use crossterm_utils::Result;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(not(target_os = "redox"))]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use std::{io, mem};
+
+use crossterm_utils::{ErrorKind, Result};
+use lazy_static::lazy_static;
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+use libc::cfmakeraw;
+use libc::{
+    c_int, c_void, isatty, poll, pollfd, read, tcflag_t, tcgetattr, tcsetattr, termios as Termios,
+    write, BRKINT, ECHO, ECHONL, ICANON, ICRNL, IEXTEN, IGNBRK, IGNCR, INLCR, ISIG, ISTRIP, IXOFF,
+    IXON, ONLCR, OPOST, PARENB, PARMRK, POLLIN, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO,
+    TCSAFLUSH, TCSANOW, VMIN, VTIME,
+};
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+use libc::{CS8, CSIZE, IMAXBEL};
+
+#[cfg(target_os = "redox")]
+use super::redox::controlling_terminal_path;
+use super::Tty;
+use crate::{termux, RawModeOptions, ScreenError, StdStream, TerminalMode};
+
+lazy_static! {
+    // All the terminal mode mutations are serialized by this lock
+    //
+    // Some(RawModeState) -> the file descriptor is in the raw mode
+    // None -> the file descriptor is not in the raw mode
+    static ref TERMINAL_MODE_PRIOR_RAW_MODE: Mutex<HashMap<RawFd, RawModeState>> = Mutex::new(HashMap::new());
+
+    // The controlling terminal, used when the standard input is not a terminal (redirected)
+    static ref CONTROLLING_TERMINAL: io::Result<File> = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(controlling_terminal_path());
+
+    // The terminal the raw mode is applied to
+    static ref TERMINAL_BACKEND: Mutex<Arc<dyn TerminalBackend>> = Mutex::new(Arc::new(LibcTerminal));
+}
+
+// The terminal found by `RawModeCommand::new`, -1 if it wasn't looked up yet
+static TERMINAL: AtomicI32 = AtomicI32::new(-1);
+
+// The terminal operations the raw mode is built on, the real terminal is used unless
+// a `RecordingScreen` replaces it
+pub(crate) trait TerminalBackend: Send + Sync {
+    // Checks whether the file descriptor is a terminal (`isatty`)
+    fn is_tty(&self, fd: RawFd) -> bool;
+    // Returns the terminal attributes (`tcgetattr`)
+    fn attributes(&self, fd: RawFd) -> Result<Termios>;
+    // Sets the terminal attributes (`tcsetattr`)
+    fn set_attributes(&self, fd: RawFd, action: c_int, termios: &Termios) -> Result<()>;
+}
+
+// The real terminal
+struct LibcTerminal;
+
+impl TerminalBackend for LibcTerminal {
+    fn is_tty(&self, fd: RawFd) -> bool {
+        unsafe { isatty(fd) == 1 }
+    }
+
+    fn attributes(&self, fd: RawFd) -> Result<Termios> {
+        let mut termios = unsafe { mem::zeroed() };
+        retry_on_interrupt(|| unsafe { tcgetattr(fd, &mut termios) })?;
+        Ok(termios)
+    }
+
+    fn set_attributes(&self, fd: RawFd, action: c_int, termios: &Termios) -> Result<()> {
+        retry_on_interrupt(|| unsafe { tcsetattr(fd, action, termios) })
+    }
+}
+
+// Returns the terminal the raw mode is applied to
+pub(crate) fn terminal() -> Arc<dyn TerminalBackend> {
+    TERMINAL_BACKEND
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+// Replaces the terminal the raw mode is applied to and returns the previous one
+//
+// The terminal looked up so far and the raw mode states are forgotten, like with the
+// `set_console_backend` function on Windows.
+#[cfg(feature = "recording")]
+pub(crate) fn set_terminal_backend(backend: Arc<dyn TerminalBackend>) -> Arc<dyn TerminalBackend> {
+    let previous = mem::replace(
+        &mut *TERMINAL_BACKEND.lock().unwrap_or_else(|e| e.into_inner()),
+        backend,
+    );
+
+    TERMINAL.store(-1, Ordering::SeqCst);
+    lock_modes().clear();
+    previous
+}
+
+// Locks the raw mode states, a poisoned lock is recovered (the states are always consistent)
+fn lock_modes() -> MutexGuard<'static, HashMap<Tty, RawModeState>> {
+    TERMINAL_MODE_PRIOR_RAW_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+struct RawModeState {
+    // The exact mode prior the raw mode
+    original: Termios,
+    // The raw mode
+    raw: Termios,
+    // `TCSANOW` or `TCSAFLUSH` (the pending input is discarded)
+    action: c_int,
+}
 
 /// This command is used for enabling and disabling raw mode for the terminal.
-pub struct RawModeCommand;
+///
+/// The whole `termios` structure is captured when the raw mode is enabled and
+/// the very same structure is restored when the raw mode is disabled. User
+/// customized settings (like `VERASE` or `IUTF8`) are preserved.
+///
+/// All the terminal mode mutations (read-modify-write of the `termios`) are serialized
+/// by an internal lock, it's safe to use the commands from several threads.
+#[derive(Clone, Copy)]
+pub struct RawModeCommand {
+    fd: RawFd,
+    options: RawModeOptions,
+}
 
 impl RawModeCommand {
-    pub fn new() -> Self {
-        RawModeCommand
+    /// Creates a command for the terminal the process is attached to.
+    ///
+    /// The first standard stream which is a terminal is used (input, output, error),
+    /// the controlling terminal (`/dev/tty`, `$TTY` on Redox, the standard input on
+    /// Termux) otherwise. It allows programs reading
+    /// piped data to render a TUI as well. Fails with the `ScreenError::NotATerminal`
+    /// error if there's no terminal at all.
+    ///
+    /// The terminal is looked up once, the following calls are cheap (no syscalls).
+    pub fn new() -> Result<Self> {
+        let cached = TERMINAL.load(Ordering::SeqCst);
+
+        if cached != -1 {
+            return Ok(RawModeCommand::from_tty(cached));
+        }
+
+        let fd = match [STDIN_FILENO, STDOUT_FILENO, STDERR_FILENO]
+            .iter()
+            .find(|fd| is_tty(**fd))
+        {
+            Some(fd) => *fd,
+            // There's no `/dev/tty` access on Android, the termios calls fail with the
+            // standard input if it's not a terminal
+            None if termux::is_termux() => STDIN_FILENO,
+            None => controlling_terminal()?,
+        };
+
+        // Keep it last - remember the terminal only if there's one
+        TERMINAL.store(fd, Ordering::SeqCst);
+
+        Ok(RawModeCommand::from_tty(fd))
+    }
+
+    /// Creates a command for the given file descriptor.
+    ///
+    /// Standard streams are mapped to the terminal the process is attached to. Fails
+    /// with the `ScreenError::NotATerminal` error if the file descriptor is not a terminal.
+    pub fn from_fd(fd: RawFd) -> Result<Self> {
+        match fd {
+            STDIN_FILENO | STDOUT_FILENO | STDERR_FILENO => RawModeCommand::new(),
+            fd if is_tty(fd) => Ok(RawModeCommand::from_tty(fd)),
+            _ => Err(ScreenError::NotATerminal.into()),
+        }
+    }
+
+    /// Creates a command for the terminal returned by the [`tty`](#method.tty) method.
+    pub fn from_tty(tty: Tty) -> Self {
+        RawModeCommand {
+            fd: tty,
+            options: RawModeOptions::raw(),
+        }
+    }
+
+    /// Sets the raw mode options.
+    pub fn with_options(mut self, options: RawModeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the terminal this command is applied to.
+    pub fn tty(&self) -> Tty {
+        self.fd
+    }
+
+    /// Checks whether both commands switch the same terminal to the same mode.
+    ///
+    /// The options which don't change the mode (like the exclusivity) are ignored.
+    pub fn same_mode(&self, other: &RawModeCommand) -> bool {
+        let mode = |options: RawModeOptions| RawModeOptions {
+            job_control: false,
+            exclusive: false,
+            flush_input: false,
+            ..options
+        };
+
+        self.fd == other.fd && mode(self.options) == mode(other.options)
+    }
+
+    /// Returns the raw mode attributes, the hook can adjust them.
+    ///
+    /// The raw mode is computed from the current attributes, but it's not applied (see
+    /// the [`enable`](#method.enable) method). The hook is called without any lock held.
+    pub fn raw_attributes_with<F: FnOnce(&mut Termios)>(&self, hook: F) -> Result<Termios> {
+        let mut ios = get_terminal_attr(self.fd)?;
+
+        if self.options.disables_everything() {
+            raw_terminal_attr(&mut ios);
+        } else {
+            options_terminal_attr(&mut ios, &self.options);
+        }
+
+        // `VMIN` & `VTIME` are ignored in the canonical mode, they're the very same slots
+        // as `VEOF` & `VEOL` on illumos & Solaris (the end of file character would be lost)
+        if ios.c_lflag & ICANON == 0 {
+            if let Some(vmin) = self.options.read_minimum {
+                ios.c_cc[VMIN] = vmin;
+            }
+
+            if let Some(vtime) = self.options.read_timeout {
+                ios.c_cc[VTIME] = vtime;
+            }
+        }
+
+        hook(&mut ios);
+
+        Ok(ios)
     }
 
-    /// Enables raw mode.
-    pub fn enable(&mut self) -> Result<()> {
-        crossterm_utils::sys::unix::enable_raw_mode()?;
+    /// Enables raw mode, the given attributes are applied.
+    ///
+    /// It's a noop if the raw mode is already enabled.
+    pub fn enable(&mut self, raw: &Termios) -> Result<()> {
+        let mut original_modes = lock_modes();
+
+        if original_modes.contains_key(&self.fd) {
+            return Ok(());
+        }
+
+        let original_mode_ios = get_terminal_attr(self.fd)?;
+
+        let action = if self.options.flush_input {
+            TCSAFLUSH
+        } else {
+            TCSANOW
+        };
+
+        set_terminal_attr_with(self.fd, action, raw)?;
+
+        // Keep it last - set the original mode only if we were able to switch to the raw mode
+        original_modes.insert(
+            self.fd,
+            RawModeState {
+                original: original_mode_ios,
+                raw: *raw,
+                action,
+            },
+        );
+
         Ok(())
     }
 
     /// Disables raw mode.
     pub fn disable(&mut self) -> Result<()> {
-        crossterm_utils::sys::unix::disable_raw_mode()?;
+        let mut original_modes = lock_modes();
+
+        if let Some(state) = original_modes.get(&self.fd) {
+            set_terminal_attr_with(self.fd, state.action, &state.original)?;
+            // Keep it last - remove the original mode only if we were able to switch back
+            original_modes.remove(&self.fd);
+        }
+
         Ok(())
     }
+
+    /// Disables raw mode of all the terminals.
+    pub fn disable_all() -> Result<()> {
+        let mut original_modes = lock_modes();
+        let mut result = Ok(());
+
+        // Keep the original modes of the terminals we weren't able to switch back
+        original_modes.retain(|fd, state| {
+            match set_terminal_attr_with(*fd, state.action, &state.original) {
+                Ok(()) => false,
+                Err(e) => {
+                    result = Err(e);
+                    true
+                }
+            }
+        });
+
+        result
+    }
+
+    /// Temporarily switches back to the mode prior the raw mode.
+    ///
+    /// The raw mode is still considered to be enabled, use the [`resume`](#method.resume)
+    /// method to switch back to it.
+    pub fn suspend(&self) -> Result<()> {
+        let original_modes = lock_modes();
+
+        match original_modes.get(&self.fd) {
+            Some(state) => set_terminal_attr_with(self.fd, state.action, &state.original),
+            None => Ok(()),
+        }
+    }
+
+    /// Switches back to the raw mode suspended with the [`suspend`](#method.suspend) method.
+    pub fn resume(&self) -> Result<()> {
+        let original_modes = lock_modes();
+
+        match original_modes.get(&self.fd) {
+            Some(state) => set_terminal_attr_with(self.fd, state.action, &state.raw),
+            None => Ok(()),
+        }
+    }
+
+    /// Enables or disables the echo (`ECHO`) only.
+    ///
+    /// The rest of the terminal mode is left untouched.
+    pub fn set_echo(&self, enabled: bool) -> Result<()> {
+        let _modes = lock_modes();
+
+        let mut termios = get_terminal_attr(self.fd)?;
+
+        if enabled {
+            termios.c_lflag |= ECHO;
+        } else {
+            termios.c_lflag &= !ECHO;
+        }
+
+        set_terminal_attr(self.fd, &termios)
+    }
+
+    /// Returns the current input mode of the terminal.
+    ///
+    /// The canonical mode (`ICANON`) means cooked, the signal processing (`ISIG`)
+    /// without the canonical mode means cbreak and raw otherwise.
+    pub fn current_mode(&self) -> Result<TerminalMode> {
+        let termios = get_terminal_attr(self.fd)?;
+
+        if termios.c_lflag & ICANON != 0 {
+            Ok(TerminalMode::Cooked)
+        } else if termios.c_lflag & ISIG != 0 {
+            Ok(TerminalMode::Cbreak)
+        } else {
+            Ok(TerminalMode::Raw)
+        }
+    }
+
+    /// Returns the current terminal attributes.
+    pub fn attributes(&self) -> Result<Termios> {
+        get_terminal_attr(self.fd)
+    }
+
+    /// Sets the terminal attributes.
+    pub fn set_attributes(&self, termios: &Termios) -> Result<()> {
+        let _modes = lock_modes();
+        set_terminal_attr(self.fd, termios)
+    }
+
+    /// Returns the attributes prior the raw mode and the raw mode attributes.
+    ///
+    /// `None` is returned if the raw mode wasn't enabled by this crate.
+    pub fn raw_mode_attributes(&self) -> Option<(Termios, Termios)> {
+        lock_modes()
+            .get(&self.fd)
+            .map(|state| (state.original, state.raw))
+    }
+
+    /// Checks whether the terminal is in the raw mode.
+    ///
+    /// The terminal is considered to be in the raw mode if both the canonical mode
+    /// (`ICANON`) and the echo (`ECHO`) are disabled.
+    pub fn is_enabled(&self) -> Result<bool> {
+        let termios = get_terminal_attr(self.fd)?;
+        Ok(termios.c_lflag & (ICANON | ECHO) == 0)
+    }
+
+    /// Returns the terminal window size (`TIOCGWINSZ`).
+    ///
+    /// The serial consoles of illumos & Solaris report no size, `$COLUMNS` & `$LINES`
+    /// are used there.
+    pub fn window_size(&self) -> Result<libc::winsize> {
+        let mut size: libc::winsize = unsafe { mem::zeroed() };
+
+        retry_on_interrupt(|| unsafe { libc::ioctl(self.fd, libc::TIOCGWINSZ, &mut size) })?;
+
+        #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+        {
+            if size.ws_col == 0 || size.ws_row == 0 {
+                let variable =
+                    |name: &str| -> Option<u16> { std::env::var(name).ok()?.parse().ok() };
+
+                if let (Some(columns), Some(rows)) = (variable("COLUMNS"), variable("LINES")) {
+                    size.ws_col = columns;
+                    size.ws_row = rows;
+                }
+            }
+        }
+
+        Ok(size)
+    }
+
+    /// Writes the request to the terminal and reads the response up to the terminator.
+    ///
+    /// The terminal must be in the raw mode. `None` is returned if the terminal doesn't
+    /// respond within the timeout.
+    pub fn query(
+        &self,
+        request: &str,
+        terminator: u8,
+        timeout: Duration,
+    ) -> Result<Option<Vec<u8>>> {
+        let mut written = 0;
+        let request = request.as_bytes();
+
+        while written < request.len() {
+            let remaining = &request[written..];
+            let result = unsafe {
+                write(
+                    self.fd,
+                    remaining.as_ptr() as *const c_void,
+                    remaining.len(),
+                )
+            };
+
+            if result == -1 {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(ErrorKind::IoError(error));
+                }
+            } else {
+                written += result as usize;
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut response = Vec::new();
+        let mut buffer = [0u8; 32];
+
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+
+            let mut fds = pollfd {
+                fd: self.fd,
+                events: POLLIN,
+                revents: 0,
+            };
+            let timeout = (deadline - now).as_millis() as c_int;
+
+            match unsafe { poll(&mut fds, 1, timeout) } {
+                -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+                -1 => return Err(ErrorKind::IoError(io::Error::last_os_error())),
+                0 => return Ok(None),
+                _ => {}
+            }
+
+            let result = unsafe { read(self.fd, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+
+            match result {
+                -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+                -1 => return Err(ErrorKind::IoError(io::Error::last_os_error())),
+                0 => return Ok(None),
+                read => {
+                    let read = &buffer[..read as usize];
+                    response.extend_from_slice(read);
+
+                    if read.contains(&terminator) {
+                        return Ok(Some(response));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the named flags of the given attributes and whether they're set.
+pub fn attribute_flags(termios: &Termios) -> Vec<(&'static str, bool)> {
+    let input = [
+        ("IGNBRK", IGNBRK),
+        ("BRKINT", BRKINT),
+        ("PARMRK", PARMRK),
+        ("ISTRIP", ISTRIP),
+        ("INLCR", INLCR),
+        ("IGNCR", IGNCR),
+        ("ICRNL", ICRNL),
+        ("IXON", IXON),
+        ("IXOFF", IXOFF),
+    ];
+    let output = [("OPOST", OPOST), ("ONLCR", ONLCR)];
+    let control = [("PARENB", PARENB)];
+    let local = [
+        ("ECHO", ECHO),
+        ("ECHONL", ECHONL),
+        ("ICANON", ICANON),
+        ("ISIG", ISIG),
+        ("IEXTEN", IEXTEN),
+    ];
+
+    let flags = |field, names: &[(&'static str, tcflag_t)]| {
+        names
+            .iter()
+            .map(|(name, flag)| (*name, field & flag != 0))
+            .collect::<Vec<_>>()
+    };
+
+    let mut result = flags(termios.c_iflag, &input);
+    result.extend(flags(termios.c_oflag, &output));
+    result.extend(flags(termios.c_cflag, &control));
+    result.extend(flags(termios.c_lflag, &local));
+    result
+}
+
+fn is_tty(fd: RawFd) -> bool {
+    terminal().is_tty(fd)
+}
+
+/// Checks whether the standard stream is a terminal (`isatty`).
+pub fn is_std_tty(stream: StdStream) -> bool {
+    is_tty(match stream {
+        StdStream::Stdin => STDIN_FILENO,
+        StdStream::Stdout => STDOUT_FILENO,
+        StdStream::Stderr => STDERR_FILENO,
+    })
+}
+
+// There's no controlling terminal if the process was started without one (like a CI job)
+#[cfg(not(target_os = "redox"))]
+fn controlling_terminal_path() -> PathBuf {
+    PathBuf::from("/dev/tty")
+}
+
+fn controlling_terminal() -> Result<RawFd> {
+    match CONTROLLING_TERMINAL.as_ref() {
+        Ok(file) => Ok(file.as_raw_fd()),
+        Err(_) => Err(ScreenError::NotATerminal.into()),
+    }
+}
+
+// Converts the `-1` returned by the libc functions into a `Result`
+pub(crate) fn wrap_with_result(t: i32) -> Result<()> {
+    if t == -1 {
+        Err(ErrorKind::IoError(io::Error::last_os_error()))
+    } else {
+        Ok(())
+    }
+}
+
+// Calls the function again if it was interrupted by a signal (`EINTR`), like `SIGWINCH`
+fn retry_on_interrupt(mut f: impl FnMut() -> i32) -> Result<()> {
+    loop {
+        if f() != -1 {
+            return Ok(());
+        }
+
+        let error = io::Error::last_os_error();
+
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(ErrorKind::IoError(error));
+        }
+    }
+}
+
+/// Transforms the given mode into the raw (non-canonical) mode.
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+fn raw_terminal_attr(termios: &mut Termios) {
+    unsafe { cfmakeraw(termios) }
+}
+
+/// Transforms the given mode into the raw (non-canonical) mode, like `cfmakeraw`.
+///
+/// There's no `cfmakeraw` on illumos & Solaris. The `VMIN` & `VTIME` slots are the
+/// `VEOF` & `VEOL` ones there, a read would wait for 4 bytes (`VEOF` is `Ctrl+D`) if
+/// they're not set.
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+fn raw_terminal_attr(termios: &mut Termios) {
+    termios.c_iflag &=
+        !(IMAXBEL | IGNBRK | BRKINT | PARMRK | ISTRIP | INLCR | IGNCR | ICRNL | IXON);
+    termios.c_oflag &= !OPOST;
+    termios.c_lflag &= !(ECHO | ECHONL | ICANON | ISIG | IEXTEN);
+    termios.c_cflag &= !(CSIZE | PARENB);
+    termios.c_cflag |= CS8;
+    termios.c_cc[VMIN] = 1;
+    termios.c_cc[VTIME] = 0;
+}
+
+/// Transforms the given mode into a mode with the given features disabled.
+fn options_terminal_attr(termios: &mut Termios, options: &RawModeOptions) {
+    if !options.echo {
+        termios.c_lflag &= !(ECHO | ECHONL);
+    }
+
+    if !options.canonical {
+        termios.c_lflag &= !ICANON;
+        termios.c_cc[VMIN] = 1;
+        termios.c_cc[VTIME] = 0;
+    }
+
+    if !options.signals {
+        termios.c_lflag &= !(ISIG | IEXTEN);
+        termios.c_iflag &= !BRKINT;
+    }
+
+    if !options.flow_control {
+        termios.c_iflag &= !(IXON | IXOFF);
+    }
+
+    if !options.output_processing {
+        termios.c_oflag &= !OPOST;
+    }
+}
+
+fn get_terminal_attr(fd: RawFd) -> Result<Termios> {
+    terminal().attributes(fd)
+}
+
+fn set_terminal_attr(fd: RawFd, termios: &Termios) -> Result<()> {
+    set_terminal_attr_with(fd, TCSANOW, termios)
+}
+
+fn set_terminal_attr_with(fd: RawFd, action: c_int, termios: &Termios) -> Result<()> {
+    terminal().set_attributes(fd, action, termios)
 }
@@ -1,49 +1,672 @@
-use crossterm_utils::Result;
-use crossterm_winapi::{ConsoleMode, Handle};
-use winapi::shared::minwindef::DWORD;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::os::windows::io::RawHandle;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crossterm_utils::{ErrorKind, Result};
+use crossterm_winapi::{ConsoleMode, Handle, ScreenBuffer};
+use lazy_static::lazy_static;
+use winapi::shared::minwindef::{BOOL, DWORD, TRUE};
+use winapi::um::consoleapi::GetConsoleMode;
+use winapi::um::processenv::GetStdHandle;
+use winapi::um::winbase::{STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE};
 use winapi::um::wincon;
+use winapi::um::winnt::HANDLE;
+
+use self::wincon::{
+    FlushConsoleInputBuffer, GetConsoleScreenBufferInfo, SetConsoleCursorPosition,
+    SetConsoleScreenBufferSize, SetConsoleTextAttribute, SetConsoleWindowInfo,
+    CONSOLE_SCREEN_BUFFER_INFO, COORD, ENABLE_ECHO_INPUT, ENABLE_EXTENDED_FLAGS,
+    ENABLE_INSERT_MODE, ENABLE_LINE_INPUT, ENABLE_MOUSE_INPUT, ENABLE_PROCESSED_INPUT,
+    ENABLE_QUICK_EDIT_MODE, ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_WINDOW_INPUT,
+};
+
+use super::{msys, Tty};
+use crate::{ConsoleInputModeFlags, RawModeOptions, ScreenError, StdStream, TerminalMode};
+
+lazy_static! {
+    // All the console mode mutations are serialized by this lock
+    //
+    // Some(RawModeState) -> the handle is in the raw mode
+    // None -> the handle is not in the raw mode
+    static ref CONSOLE_MODE_PRIOR_RAW_MODE: Mutex<HashMap<Tty, RawModeState>> = Mutex::new(HashMap::new());
+
+    // The console input found by `RawModeCommand::new` and whether it's a Cygwin/MSYS
+    // pseudo terminal, `None` if it wasn't looked up yet
+    static ref CONSOLE_INPUT: Mutex<Option<(Tty, bool)>> = Mutex::new(None);
+
+    // The console the raw mode & the legacy alternate screen are applied to
+    static ref CONSOLE_BACKEND: Mutex<Arc<dyn ConsoleBackend>> = Mutex::new(Arc::new(WinApiConsole));
+}
+
+/// The console operations the raw mode & the legacy alternate screen (the console screen
+/// buffers) are built on.
+///
+/// The real console is used by default, replace it with the
+/// [`set_console_backend`](fn.set_console_backend.html) function (like with the
+/// [`FakeConsole`](struct.FakeConsole.html) in the tests).
+pub trait ConsoleBackend: Send + Sync {
+    /// Returns the console input handle (`CONIN$`).
+    fn input_handle(&self) -> Result<RawHandle>;
+    /// Returns the console output handle, the main screen buffer (`CONOUT$`).
+    fn output_handle(&self) -> Result<RawHandle>;
+    /// Returns the console mode of the handle (`GetConsoleMode`).
+    fn mode(&self, handle: RawHandle) -> Result<DWORD>;
+    /// Sets the console mode of the handle (`SetConsoleMode`).
+    fn set_mode(&self, handle: RawHandle, mode: DWORD) -> Result<()>;
+    /// Discards the pending input (`FlushConsoleInputBuffer`).
+    fn flush_input(&self, handle: RawHandle) -> Result<()>;
+    /// Creates a new screen buffer (`CreateConsoleScreenBuffer`).
+    fn create_screen_buffer(&self) -> Result<RawHandle>;
+    /// Makes the screen buffer the active one (`SetConsoleActiveScreenBuffer`).
+    fn show_screen_buffer(&self, handle: RawHandle) -> Result<()>;
+    /// Returns the screen buffer info (`GetConsoleScreenBufferInfo`).
+    fn screen_buffer_info(&self, handle: RawHandle) -> Result<CONSOLE_SCREEN_BUFFER_INFO>;
+    /// Resizes the screen buffer (`SetConsoleScreenBufferSize`).
+    fn set_screen_buffer_size(&self, handle: RawHandle, size: COORD) -> Result<()>;
+    /// Restores the attributes, the window, and the cursor position of the screen buffer
+    /// from the screen buffer info.
+    fn restore_screen_buffer(
+        &self,
+        handle: RawHandle,
+        info: &CONSOLE_SCREEN_BUFFER_INFO,
+    ) -> Result<()>;
+}
+
+// The real console
+struct WinApiConsole;
+
+impl ConsoleBackend for WinApiConsole {
+    fn input_handle(&self) -> Result<RawHandle> {
+        Ok(Handle::input_handle()? as RawHandle)
+    }
+
+    fn output_handle(&self) -> Result<RawHandle> {
+        Ok(Handle::output_handle()? as RawHandle)
+    }
+
+    fn mode(&self, handle: RawHandle) -> Result<DWORD> {
+        Ok(ConsoleMode::from(handle as HANDLE).mode()?)
+    }
+
+    fn set_mode(&self, handle: RawHandle, mode: DWORD) -> Result<()> {
+        ConsoleMode::from(handle as HANDLE).set_mode(mode)?;
+        Ok(())
+    }
+
+    fn flush_input(&self, handle: RawHandle) -> Result<()> {
+        result(unsafe { FlushConsoleInputBuffer(handle as HANDLE) })
+    }
+
+    fn create_screen_buffer(&self) -> Result<RawHandle> {
+        let screen_buffer = ScreenBuffer::create();
+        Ok(*screen_buffer.handle() as RawHandle)
+    }
+
+    fn show_screen_buffer(&self, handle: RawHandle) -> Result<()> {
+        ScreenBuffer::from(handle as HANDLE).show()?;
+        Ok(())
+    }
+
+    fn screen_buffer_info(&self, handle: RawHandle) -> Result<CONSOLE_SCREEN_BUFFER_INFO> {
+        let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { mem::zeroed() };
+        result(unsafe { GetConsoleScreenBufferInfo(handle as HANDLE, &mut info) })?;
+        Ok(info)
+    }
+
+    fn set_screen_buffer_size(&self, handle: RawHandle, size: COORD) -> Result<()> {
+        result(unsafe { SetConsoleScreenBufferSize(handle as HANDLE, size) })
+    }
+
+    fn restore_screen_buffer(
+        &self,
+        handle: RawHandle,
+        info: &CONSOLE_SCREEN_BUFFER_INFO,
+    ) -> Result<()> {
+        let handle = handle as HANDLE;
+        result(unsafe { SetConsoleTextAttribute(handle, info.wAttributes) })?;
+        result(unsafe { SetConsoleWindowInfo(handle, TRUE, &info.srWindow) })?;
+        result(unsafe { SetConsoleCursorPosition(handle, info.dwCursorPosition) })
+    }
+}
+
+// Returns the console the raw mode & the legacy alternate screen are applied to
+pub(crate) fn console() -> Arc<dyn ConsoleBackend> {
+    CONSOLE_BACKEND
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Replaces the console the raw mode & the legacy alternate screen are applied to, `None`
+/// switches back to the real console.
+///
+/// The console input & output handles looked up so far and the raw mode states are
+/// forgotten, replace the console when neither the raw mode nor the alternate screen is
+/// enabled. The [`ScreenBufferManager`](struct.ScreenBufferManager.html) and the screen
+/// reading & writing functions (like the [`Presenter`](struct.Presenter.html)) are not
+/// covered, they require the real console.
+///
+/// Windows only, available with the `recording` feature only.
+#[cfg(feature = "recording")]
+pub fn set_console_backend(backend: Option<Arc<dyn ConsoleBackend>>) {
+    let backend = backend.unwrap_or_else(|| Arc::new(WinApiConsole));
+    *CONSOLE_BACKEND.lock().unwrap_or_else(|e| e.into_inner()) = backend;
+
+    CONSOLE_INPUT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    lock_modes().clear();
+    crate::alternate::windows::forget_main_screen_buffer();
+}
+
+// Locks the raw mode states, a poisoned lock is recovered (the states are always consistent)
+fn lock_modes() -> MutexGuard<'static, HashMap<Tty, RawModeState>> {
+    CONSOLE_MODE_PRIOR_RAW_MODE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+struct RawModeState {
+    // The exact mode prior the raw mode
+    original: DWORD,
+    // The raw mode
+    raw: DWORD,
+    // The pending input is discarded when the mode is changed
+    flush_input: bool,
+    // The `stty` settings of a Cygwin/MSYS pseudo terminal (the console modes are unused)
+    stty: Option<SttyState>,
+}
+
+struct SttyState {
+    // The settings prior the raw mode (`stty -g`)
+    original: String,
+    // The arguments enabling the raw mode
+    raw: Vec<&'static str>,
+}
 
-use self::wincon::{ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT};
+impl RawModeState {
+    // Switches back to the mode prior the raw mode
+    fn apply_original(&self, handle: Tty) -> Result<()> {
+        match &self.stty {
+            Some(stty) => msys::stty(&[&stty.original]).map(|_| ()),
+            None => set_console_mode(handle, self.original, self.flush_input),
+        }
+    }
+
+    // Switches to the raw mode
+    fn apply_raw(&self, handle: Tty) -> Result<()> {
+        match &self.stty {
+            Some(stty) => msys::stty(&stty.raw).map(|_| ()),
+            None => set_console_mode(handle, self.raw, self.flush_input),
+        }
+    }
+}
 
 /// This command is used for enabling and disabling raw mode for Windows systems.
 /// For more info check: https://docs.microsoft.com/en-us/windows/console/high-level-console-modes.
+///
+/// The console mode is captured when the raw mode is enabled and the very same
+/// mode is restored when the raw mode is disabled. Bits the user never had are
+/// not set.
+///
+/// All the console mode mutations (read-modify-write of the mode) are serialized
+/// by an internal lock, it's safe to use the commands from several threads.
 #[derive(Clone, Copy)]
 pub struct RawModeCommand {
-    mask: DWORD,
+    mask: ConsoleInputModeFlags,
+    // Flags set (instead of cleared) when the raw mode is enabled
+    extra: ConsoleInputModeFlags,
+    flush_input: bool,
+    // The handle address is stored to keep the command `Send` and `Sync`
+    handle: Tty,
+    // `true` if the handle is a Cygwin/MSYS pseudo terminal (like Mintty)
+    msys: bool,
 }
 
 impl RawModeCommand {
-    pub fn new() -> Self {
+    /// Creates a command for the console input handle.
+    ///
+    /// The Cygwin/MSYS pseudo terminal (like Mintty) of the standard input is used if
+    /// it's not a console, its tty attributes are changed via `stty`. Fails with the
+    /// `ScreenError::NotATerminal` error if the input is neither a console nor a pseudo
+    /// terminal.
+    ///
+    /// The console input is looked up once, the following calls are cheap (no syscalls).
+    pub fn new() -> Result<Self> {
+        let mut console_input = CONSOLE_INPUT.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (tty, msys) = match *console_input {
+            Some(cached) => cached,
+            None => {
+                let input = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+
+                let found = if msys::is_msys_pty(input) {
+                    (input as Tty, true)
+                } else {
+                    (
+                        RawModeCommand::from_console_handle(console().input_handle()? as HANDLE)?
+                            .handle,
+                        false,
+                    )
+                };
+
+                // Keep it last - remember the console input only if there's one
+                *console_input = Some(found);
+                found
+            }
+        };
+
+        Ok(RawModeCommand {
+            msys,
+            ..RawModeCommand::from_tty(tty)
+        })
+    }
+
+    /// Creates a command for the given handle.
+    ///
+    /// Standard handles are mapped to the console input handle. Fails with the
+    /// `ScreenError::NotATerminal` error if the handle is not a console.
+    pub fn from_handle(handle: RawHandle) -> Result<Self> {
+        let handle = handle as HANDLE;
+
+        let is_std_handle = [STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+            .iter()
+            .any(|std_handle| unsafe { GetStdHandle(*std_handle) } == handle);
+
+        if is_std_handle {
+            RawModeCommand::new()
+        } else {
+            RawModeCommand::from_console_handle(handle)
+        }
+    }
+
+    // Checks whether the handle is a console handle (`GetConsoleMode` succeeds)
+    fn from_console_handle(handle: HANDLE) -> Result<Self> {
+        if console().mode(handle as RawHandle).is_err() {
+            return Err(ScreenError::NotATerminal.into());
+        }
+
+        Ok(RawModeCommand::from_tty(handle as Tty))
+    }
+
+    /// Creates a command for the handle returned by the [`tty`](#method.tty) method.
+    pub fn from_tty(tty: Tty) -> Self {
         RawModeCommand {
-            mask: ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT,
+            mask: ConsoleInputModeFlags::raw_mode(),
+            extra: ConsoleInputModeFlags::empty(),
+            flush_input: false,
+            handle: tty,
+            msys: false,
         }
     }
+
+    /// Sets the raw mode options.
+    ///
+    /// The flow control and the output processing options are ignored.
+    pub fn with_options(self, options: RawModeOptions) -> Self {
+        let mut mask = ConsoleInputModeFlags::empty();
+        mask.set(ConsoleInputModeFlags::ECHO_INPUT, !options.echo);
+        mask.set(ConsoleInputModeFlags::LINE_INPUT, !options.canonical);
+        mask.set(ConsoleInputModeFlags::PROCESSED_INPUT, !options.signals);
+        mask.set(ConsoleInputModeFlags::QUICK_EDIT_MODE, !options.quick_edit);
+        mask.set(ConsoleInputModeFlags::INSERT_MODE, !options.insert_mode);
+        mask.set(ConsoleInputModeFlags::MOUSE_INPUT, !options.mouse_input);
+
+        self.with_mask(mask)
+            .with_virtual_terminal_input(options.virtual_terminal_input)
+            .with_flush_input(options.flush_input)
+    }
+
+    /// Discards the pending input when the raw mode is enabled or disabled.
+    pub fn with_flush_input(mut self, enabled: bool) -> Self {
+        self.flush_input = enabled;
+        self
+    }
+
+    /// Sets the flags cleared when the raw mode is enabled.
+    ///
+    /// The `ENABLE_EXTENDED_FLAGS` flag is set whenever the Quick Edit mode or the insert
+    /// mode is cleared, otherwise the change is ignored by the console.
+    pub fn with_mask(mut self, mask: ConsoleInputModeFlags) -> Self {
+        self.mask = mask;
+
+        let extended = ConsoleInputModeFlags::QUICK_EDIT_MODE | ConsoleInputModeFlags::INSERT_MODE;
+        self.extra.set(
+            ConsoleInputModeFlags::EXTENDED_FLAGS,
+            mask.intersects(extended),
+        );
+        self
+    }
+
+    /// Sets the `ENABLE_VIRTUAL_TERMINAL_INPUT` flag when the raw mode is enabled.
+    ///
+    /// Keys are reported as VT escape sequences then (Windows 10+ only).
+    pub fn with_virtual_terminal_input(mut self, enabled: bool) -> Self {
+        self.extra
+            .set(ConsoleInputModeFlags::VIRTUAL_TERMINAL_INPUT, enabled);
+        self
+    }
+
+    /// Returns the handle this command is applied to.
+    pub fn tty(&self) -> Tty {
+        self.handle
+    }
+
+    /// Checks whether both commands switch the same console to the same mode.
+    pub fn same_mode(&self, other: &RawModeCommand) -> bool {
+        self.handle == other.handle && self.mask == other.mask && self.extra == other.extra
+    }
+
+    fn handle(&self) -> RawHandle {
+        self.handle as RawHandle
+    }
 }
 
 impl RawModeCommand {
-    /// Enables raw mode.
-    pub fn enable(&mut self) -> Result<()> {
-        let console_mode = ConsoleMode::from(Handle::input_handle()?);
+    /// Returns the raw mode, the hook can adjust it.
+    ///
+    /// The raw mode is computed from the current console mode, but it's not applied (see
+    /// the [`enable`](#method.enable) method). The hook is called without any lock held.
+    /// The hook is not called for a Cygwin/MSYS pseudo terminal (the mode is unused).
+    pub fn raw_attributes_with<F: FnOnce(&mut DWORD)>(&self, hook: F) -> Result<DWORD> {
+        if self.msys {
+            return Ok(0);
+        }
 
-        let dw_mode = console_mode.mode()?;
+        let dw_mode = console().mode(self.handle())?;
+        let mut new_mode = (dw_mode & !self.mask.bits()) | self.extra.bits();
 
-        let new_mode = dw_mode & !self.mask;
+        hook(&mut new_mode);
 
-        console_mode.set_mode(new_mode)?;
+        Ok(new_mode)
+    }
+
+    /// Enables raw mode, the given console mode is applied.
+    ///
+    /// It's a noop if the raw mode is already enabled.
+    pub fn enable(&mut self, raw: &DWORD) -> Result<()> {
+        let mut original_modes = lock_modes();
+
+        if original_modes.contains_key(&self.handle) {
+            return Ok(());
+        }
+
+        if self.msys {
+            let original = msys::stty(&["-g"])?;
+            let raw = msys::raw_mode_arguments(self.mask);
+            msys::stty(&raw)?;
+
+            // Keep it last - set the original mode only if we were able to switch to the raw mode
+            original_modes.insert(
+                self.handle,
+                RawModeState {
+                    original: 0,
+                    raw: 0,
+                    flush_input: self.flush_input,
+                    stty: Some(SttyState { original, raw }),
+                },
+            );
+
+            return Ok(());
+        }
+
+        let dw_mode = console().mode(self.handle())?;
+
+        set_console_mode(self.handle, *raw, self.flush_input)?;
+
+        // Keep it last - set the original mode only if we were able to switch to the raw mode
+        original_modes.insert(
+            self.handle,
+            RawModeState {
+                original: dw_mode,
+                raw: *raw,
+                flush_input: self.flush_input,
+                stty: None,
+            },
+        );
 
         Ok(())
     }
 
     /// Disables raw mode.
-    pub fn disable(&self) -> Result<()> {
-        let console_mode = ConsoleMode::from(Handle::input_handle()?);
+    pub fn disable(&mut self) -> Result<()> {
+        let mut original_modes = lock_modes();
 
-        let dw_mode = console_mode.mode()?;
+        if let Some(state) = original_modes.get(&self.handle) {
+            state.apply_original(self.handle)?;
+            // Keep it last - remove the original mode only if we were able to switch back
+            original_modes.remove(&self.handle);
+        }
+
+        Ok(())
+    }
+
+    /// Disables raw mode of all the handles.
+    pub fn disable_all() -> Result<()> {
+        let mut original_modes = lock_modes();
+        let mut result = Ok(());
 
-        let new_mode = dw_mode | self.mask;
+        // Keep the original modes of the handles we weren't able to switch back
+        original_modes.retain(|handle, state| match state.apply_original(*handle) {
+            Ok(()) => false,
+            Err(e) => {
+                result = Err(e);
+                true
+            }
+        });
+
+        result
+    }
+
+    /// Temporarily switches back to the mode prior the raw mode.
+    ///
+    /// The raw mode is still considered to be enabled, use the [`resume`](#method.resume)
+    /// method to switch back to it.
+    pub fn suspend(&self) -> Result<()> {
+        let original_modes = lock_modes();
+
+        if let Some(state) = original_modes.get(&self.handle) {
+            state.apply_original(self.handle)?;
+        }
 
-        console_mode.set_mode(new_mode)?;
+        Ok(())
+    }
+
+    /// Switches back to the raw mode suspended with the [`suspend`](#method.suspend) method.
+    pub fn resume(&self) -> Result<()> {
+        let original_modes = lock_modes();
+
+        if let Some(state) = original_modes.get(&self.handle) {
+            state.apply_raw(self.handle)?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables the echo (`ENABLE_ECHO_INPUT`) only.
+    ///
+    /// The rest of the console mode is left untouched.
+    pub fn set_echo(&self, enabled: bool) -> Result<()> {
+        let _modes = lock_modes();
+
+        if self.msys {
+            msys::stty(&[if enabled { "echo" } else { "-echo" }])?;
+            return Ok(());
+        }
+
+        let console = console();
+
+        let dw_mode = console.mode(self.handle())?;
+
+        let new_mode = if enabled {
+            dw_mode | ENABLE_ECHO_INPUT
+        } else {
+            dw_mode & !ENABLE_ECHO_INPUT
+        };
+
+        console.set_mode(self.handle(), new_mode)?;
+
+        Ok(())
+    }
+
+    /// Changes the console mode, the raw mode state follows the change.
+    ///
+    /// The `update` function maps the current mode to the new one. It's applied to the
+    /// mode prior the raw mode and to the raw mode as well, the change survives the raw
+    /// mode disable (suspend, resume). Returns the mode prior the change.
+    ///
+    /// Fails with the `ScreenError::NotATerminal` error on a Cygwin/MSYS pseudo terminal.
+    pub fn update_mode<F: Fn(DWORD) -> DWORD>(&self, update: F) -> Result<DWORD> {
+        if self.msys {
+            return Err(ScreenError::NotATerminal.into());
+        }
 
-        return Ok(());
+        let mut original_modes = lock_modes();
+        let console = console();
+
+        let dw_mode = console.mode(self.handle())?;
+        console.set_mode(self.handle(), update(dw_mode))?;
+
+        // Keep it last - update the raw mode state only if we were able to change the mode
+        if let Some(state) = original_modes.get_mut(&self.handle) {
+            state.original = update(state.original);
+            state.raw = update(state.raw);
+        }
+
+        Ok(dw_mode)
+    }
+
+    /// Returns the current input mode of the console.
+    ///
+    /// The line input (`ENABLE_LINE_INPUT`) means cooked, the processed input
+    /// (`ENABLE_PROCESSED_INPUT`) without the line input means cbreak and raw otherwise.
+    pub fn current_mode(&self) -> Result<TerminalMode> {
+        if self.msys {
+            // Only the raw mode enabled by this crate is known
+            return Ok(if lock_modes().contains_key(&self.handle) {
+                TerminalMode::Raw
+            } else {
+                TerminalMode::Cooked
+            });
+        }
+
+        let dw_mode = console().mode(self.handle())?;
+
+        if dw_mode & ENABLE_LINE_INPUT != 0 {
+            Ok(TerminalMode::Cooked)
+        } else if dw_mode & ENABLE_PROCESSED_INPUT != 0 {
+            Ok(TerminalMode::Cbreak)
+        } else {
+            Ok(TerminalMode::Raw)
+        }
+    }
+
+    /// Returns the current console mode.
+    ///
+    /// Fails with the `ScreenError::NotATerminal` error on a Cygwin/MSYS pseudo terminal.
+    pub fn attributes(&self) -> Result<DWORD> {
+        if self.msys {
+            return Err(ScreenError::NotATerminal.into());
+        }
+
+        Ok(console().mode(self.handle())?)
+    }
+
+    /// Sets the console mode.
+    ///
+    /// Fails with the `ScreenError::NotATerminal` error on a Cygwin/MSYS pseudo terminal.
+    pub fn set_attributes(&self, mode: &DWORD) -> Result<()> {
+        if self.msys {
+            return Err(ScreenError::NotATerminal.into());
+        }
+
+        let _modes = lock_modes();
+        console().set_mode(self.handle(), *mode)?;
+        Ok(())
+    }
+
+    /// Returns the console mode prior the raw mode and the raw mode.
+    ///
+    /// `None` is returned if the raw mode wasn't enabled by this crate or the console is
+    /// a Cygwin/MSYS pseudo terminal.
+    pub fn raw_mode_attributes(&self) -> Option<(DWORD, DWORD)> {
+        lock_modes()
+            .get(&self.handle)
+            .filter(|state| state.stty.is_none())
+            .map(|state| (state.original, state.raw))
+    }
+
+    /// Checks whether the console is in the raw mode.
+    ///
+    /// The console is considered to be in the raw mode if all the mask bits are cleared.
+    pub fn is_enabled(&self) -> Result<bool> {
+        if self.msys {
+            return Ok(lock_modes().contains_key(&self.handle));
+        }
+
+        let dw_mode = console().mode(self.handle())?;
+
+        Ok(dw_mode & self.mask.bits() == 0)
+    }
+}
+
+/// Checks whether the standard stream is a console (`GetConsoleMode`) or a Cygwin/MSYS
+/// pseudo terminal.
+pub fn is_std_tty(stream: StdStream) -> bool {
+    let handle = unsafe {
+        GetStdHandle(match stream {
+            StdStream::Stdin => STD_INPUT_HANDLE,
+            StdStream::Stdout => STD_OUTPUT_HANDLE,
+            StdStream::Stderr => STD_ERROR_HANDLE,
+        })
+    };
+
+    let mut mode: DWORD = 0;
+    unsafe { GetConsoleMode(handle, &mut mode) != 0 }
+    || msys::is_msys_pty(handle)
+}
+
+// Sets the console mode, the pending input is discarded if `flush_input` is set
+fn set_console_mode(handle: Tty, mode: DWORD, flush_input: bool) -> Result<()> {
+    let console = console();
+    console.set_mode(handle as RawHandle, mode)?;
+
+    if flush_input {
+        console.flush_input(handle as RawHandle)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the named flags of the given console mode and whether they're set.
+pub fn attribute_flags(mode: &DWORD) -> Vec<(&'static str, bool)> {
+    [
+        ("ENABLE_PROCESSED_INPUT", ENABLE_PROCESSED_INPUT),
+        ("ENABLE_LINE_INPUT", ENABLE_LINE_INPUT),
+        ("ENABLE_ECHO_INPUT", ENABLE_ECHO_INPUT),
+        ("ENABLE_WINDOW_INPUT", ENABLE_WINDOW_INPUT),
+        ("ENABLE_MOUSE_INPUT", ENABLE_MOUSE_INPUT),
+        ("ENABLE_INSERT_MODE", ENABLE_INSERT_MODE),
+        ("ENABLE_QUICK_EDIT_MODE", ENABLE_QUICK_EDIT_MODE),
+        ("ENABLE_EXTENDED_FLAGS", ENABLE_EXTENDED_FLAGS),
+        (
+            "ENABLE_VIRTUAL_TERMINAL_INPUT",
+            ENABLE_VIRTUAL_TERMINAL_INPUT,
+        ),
+    ]
+    .iter()
+    .map(|(name, flag)| (*name, mode & flag != 0))
+    .collect()
+}
+
+// Converts the `BOOL` returned by the WinAPI functions into a `Result`
+pub(crate) fn result(value: BOOL) -> Result<()> {
+    if value == 0 {
+        Err(ErrorKind::IoError(io::Error::last_os_error()))
+    } else {
+        Ok(())
     }
 }
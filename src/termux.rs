@@ -0,0 +1,26 @@
+//! Termux (Android) quirks.
+//!
+//! There's no `/dev/tty` access on Android, the standard input is used for the terminal
+//! modes then. The Termux terminal has no window to manipulate and no title stack, the
+//! `XTWINOPS` sequences are not written at all (they would be printed as garbage).
+
+// The Termux installation prefix (`$PREFIX`)
+#[cfg(unix)]
+const TERMUX_PREFIX: &str = "/data/data/com.termux/";
+
+// Checks whether the terminal is Termux (`$TERMUX_VERSION` is set or `$PREFIX` is the
+// Termux prefix)
+#[cfg(unix)]
+pub(crate) fn is_termux() -> bool {
+    if std::env::var_os("TERMUX_VERSION").is_some() {
+        return true;
+    }
+
+    std::env::var("PREFIX").is_ok_and(|prefix| prefix.starts_with(TERMUX_PREFIX))
+}
+
+// Checks whether the terminal is Termux, always `false` on Windows
+#[cfg(windows)]
+pub(crate) fn is_termux() -> bool {
+    false
+}
@@ -0,0 +1,233 @@
+use std::io::Write;
+use std::sync::{Mutex, MutexGuard};
+
+use crossterm_utils::{csi, write_cout, Command, ExecutableCommand, Result};
+use lazy_static::lazy_static;
+
+use crate::{dumb, restore, termux, Passthrough};
+
+const WINDOW_TITLE_STATE_CHANGE: &str = "window title";
+
+static PUSH_TITLE_CSI_SEQUENCE: &str = csi!("22;2t");
+static POP_TITLE_CSI_SEQUENCE: &str = csi!("23;2t");
+
+lazy_static! {
+    // How many titles were pushed (and not popped) via this crate
+    static ref TITLE_DEPTH: Mutex<usize> = Mutex::new(0);
+}
+
+// Locks the title stack depth, a poisoned lock is recovered (the depth is always consistent)
+fn lock_depth() -> MutexGuard<'static, usize> {
+    TITLE_DEPTH.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A command to save the window title on the terminal title stack (`XTWINOPS 22;2`).
+///
+/// Set your own title afterwards and use the [`PopTitle`](struct.PopTitle.html) command
+/// to restore the original one on exit. The titles pushed (and not popped) via the
+/// [`execute_tracked`](#method.execute_tracked) method are restored by the
+/// [`restore_all`](fn.restore_all.html) function as well.
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals without the title stack ignore it. It's a noop on Termux and on the dumb
+/// terminals (see [`DumbTerminalPolicy`](enum.DumbTerminalPolicy.html)).
+///
+/// The sequence is wrapped in the terminal multiplexer envelope, see
+/// [`Passthrough`](enum.Passthrough.html). The legacy Windows console (no ANSI support)
+/// keeps the stack in the process (`GetConsoleTitle` & `SetConsoleTitle`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{
+///     execute, Result, EnterAlternateScreen, LeaveAlternateScreen, PopTitle, PushTitle,
+/// };
+///
+/// fn main() -> Result<()> {
+///     PushTitle.execute_tracked(&mut stdout())?;
+///     execute!(stdout(), EnterAlternateScreen)?;
+///
+///     // Set the title & do anything on the alternate screen
+///
+///     execute!(stdout(), LeaveAlternateScreen)?;
+///     PopTitle.execute_tracked(&mut stdout())
+/// }
+/// ```
+pub struct PushTitle;
+
+impl PushTitle {
+    /// Saves the window title and tracks it for the [`restore_all`](fn.restore_all.html)
+    /// function.
+    ///
+    /// The title stack depth is incremented only if the sequence was written.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(PushTitle)?;
+
+        // Keep it last - track the title only if we were able to push it
+        if !is_suppressed() {
+            *lock_depth() += 1;
+            restore::register(WINDOW_TITLE_STATE_CHANGE, pop_all);
+        }
+
+        Ok(())
+    }
+}
+
+impl Command for PushTitle {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if is_suppressed() {
+            return String::new();
+        }
+
+        Passthrough::current().wrap(PUSH_TITLE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::push()
+    }
+}
+
+/// A command to restore the window title from the terminal title stack (`XTWINOPS 23;2`).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// It's a noop on Termux and on the dumb terminals.
+pub struct PopTitle;
+
+impl PopTitle {
+    /// Restores the window title saved by the
+    /// [`PushTitle::execute_tracked`](struct.PushTitle.html#method.execute_tracked) method.
+    ///
+    /// The title stack depth is decremented only if the sequence was written.
+    pub fn execute_tracked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.execute(PopTitle)?;
+
+        // Keep it last - forget the title only if we were able to pop it
+        if !is_suppressed() {
+            pop_one();
+        }
+
+        Ok(())
+    }
+}
+
+impl Command for PopTitle {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if is_suppressed() {
+            return String::new();
+        }
+
+        Passthrough::current().wrap(POP_TITLE_CSI_SEQUENCE)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        legacy::pop()
+    }
+}
+
+// Checks whether the title stack sequences must not be written
+fn is_suppressed() -> bool {
+    termux::is_termux() || dumb::suppressed()
+}
+
+// Decrements the depth, the state change is unregistered once all the titles are popped
+fn pop_one() {
+    let mut depth = lock_depth();
+    *depth = depth.saturating_sub(1);
+
+    if *depth == 0 {
+        restore::unregister(WINDOW_TITLE_STATE_CHANGE);
+    }
+}
+
+// Restores the original title (pops all the titles pushed via this crate)
+fn pop_all() -> Result<()> {
+    let depth = std::mem::replace(&mut *lock_depth(), 0);
+    restore::unregister(WINDOW_TITLE_STATE_CHANGE);
+
+    #[cfg(windows)]
+    {
+        if !crossterm_utils::supports_ansi() {
+            return legacy::pop_all();
+        }
+    }
+
+    for _ in 0..depth {
+        write_cout!(Passthrough::current().wrap(POP_TITLE_CSI_SEQUENCE))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+mod legacy {
+    use std::io;
+    use std::sync::{Mutex, MutexGuard};
+
+    use crossterm_utils::{ErrorKind, Result};
+    use lazy_static::lazy_static;
+    use winapi::um::wincon::{GetConsoleTitleW, SetConsoleTitleW};
+
+    // The longest title `GetConsoleTitle` can return
+    const MAX_TITLE_LENGTH: usize = 64 * 1024;
+
+    lazy_static! {
+        // The titles saved by the `PushTitle` command (UTF-16, nul terminated)
+        static ref TITLE_STACK: Mutex<Vec<Vec<u16>>> = Mutex::new(Vec::new());
+    }
+
+    fn lock_stack() -> MutexGuard<'static, Vec<Vec<u16>>> {
+        TITLE_STACK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(super) fn push() -> Result<()> {
+        let mut title = vec![0u16; MAX_TITLE_LENGTH];
+        let length = unsafe { GetConsoleTitleW(title.as_mut_ptr(), title.len() as u32) };
+
+        // Zero is returned for an empty title as well
+        title.truncate(length as usize);
+        title.push(0);
+
+        lock_stack().push(title);
+        Ok(())
+    }
+
+    pub(super) fn pop() -> Result<()> {
+        match lock_stack().pop() {
+            Some(title) => set_title(&title),
+            None => Ok(()),
+        }
+    }
+
+    // Restores the bottom (original) title
+    pub(super) fn pop_all() -> Result<()> {
+        let mut stack = lock_stack();
+
+        let result = match stack.first() {
+            Some(title) => set_title(title),
+            None => Ok(()),
+        };
+
+        stack.clear();
+        result
+    }
+
+    fn set_title(title: &[u16]) -> Result<()> {
+        if unsafe { SetConsoleTitleW(title.as_ptr()) } == 0 {
+            return Err(ErrorKind::IoError(io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
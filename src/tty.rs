@@ -0,0 +1,32 @@
+use crate::sys;
+
+/// A standard stream of the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StdStream {
+    /// The standard input.
+    Stdin,
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+/// Checks whether the standard stream is a terminal.
+///
+/// It's `isatty` on UNIX and `GetConsoleMode` on Windows (Cygwin/MSYS pseudo terminals,
+/// like Mintty, are terminals as well). A redirected stream (a pipe or a file) is not a
+/// terminal.
+///
+/// # Examples
+///
+/// ```no_run
+/// use crossterm_screen::{is_tty, AlternateScreen, StdStream};
+///
+/// // Don't draw the alternate screen into a file
+/// if is_tty(StdStream::Stdout) {
+///     let _alternate = AlternateScreen::to_alternate(true);
+/// }
+/// ```
+pub fn is_tty(stream: StdStream) -> bool {
+    sys::is_std_tty(stream)
+}
@@ -0,0 +1,84 @@
+#[cfg(windows)]
+use crossterm_utils::Result;
+use crossterm_utils::{csi, Command};
+
+use crate::{dumb, termux};
+
+/// A command to manipulate the terminal window (`XTWINOPS`).
+///
+/// It's useful for the presentation & demo tooling built on the alternate screen.
+/// The window position requested by the
+/// [`ReportPosition`](enum.XtWindowOp.html#variant.ReportPosition) operation is
+/// parsed by the [`window_position`](fn.window_position.html) function (UNIX).
+///
+/// # Notes
+///
+/// Commands must be executed/queued for execution otherwise they do nothing.
+///
+/// Terminals (and terminal multiplexers) often disallow the window manipulation, the
+/// operations are ignored then. It's a noop on Termux (no window), on the dumb terminals
+/// (see [`DumbTerminalPolicy`](enum.DumbTerminalPolicy.html)), and on the legacy Windows
+/// console (no ANSI support).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{stdout, Write};
+/// use crossterm_screen::{execute, Result, XtWindowOp};
+///
+/// fn main() -> Result<()> {
+///     execute!(stdout(), XtWindowOp::Raise, XtWindowOp::FullScreen)?;
+///
+///     // Run the demo
+///
+///     execute!(stdout(), XtWindowOp::UndoFullScreen)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XtWindowOp {
+    /// De-iconifies (restores) the window (`1`).
+    DeIconify,
+    /// Iconifies (minimizes) the window (`2`).
+    Iconify,
+    /// Moves the window to the given position in pixels `(x, y)` (`3`).
+    Move(u16, u16),
+    /// Raises the window to the front of the stacking order (`5`).
+    Raise,
+    /// Lowers the window to the bottom of the stacking order (`6`).
+    Lower,
+    /// Undoes the full-screen mode (`10;0`).
+    UndoFullScreen,
+    /// Switches the window to the full-screen mode (`10;1`).
+    FullScreen,
+    /// Toggles the full-screen mode (`10;2`).
+    ToggleFullScreen,
+    /// Requests the window position, the terminal responds with `CSI 3 ; x ; y t` (`13`).
+    ReportPosition,
+}
+
+impl Command for XtWindowOp {
+    type AnsiType = String;
+
+    fn ansi_code(&self) -> Self::AnsiType {
+        if termux::is_termux() || dumb::suppressed() {
+            return String::new();
+        }
+
+        match *self {
+            XtWindowOp::DeIconify => csi!("1t").to_string(),
+            XtWindowOp::Iconify => csi!("2t").to_string(),
+            XtWindowOp::Move(x, y) => format!(csi!("3;{};{}t"), x, y),
+            XtWindowOp::Raise => csi!("5t").to_string(),
+            XtWindowOp::Lower => csi!("6t").to_string(),
+            XtWindowOp::UndoFullScreen => csi!("10;0t").to_string(),
+            XtWindowOp::FullScreen => csi!("10;1t").to_string(),
+            XtWindowOp::ToggleFullScreen => csi!("10;2t").to_string(),
+            XtWindowOp::ReportPosition => csi!("13t").to_string(),
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> Result<()> {
+        Ok(())
+    }
+}
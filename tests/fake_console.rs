@@ -0,0 +1,127 @@
+//! The raw mode & the legacy alternate screen driven through the `FakeConsole`.
+#![cfg(all(windows, feature = "recording"))]
+
+use std::sync::{Arc, Mutex};
+
+use crossterm_screen::{
+    force_ansi, set_console_backend, AlternateScreen, FakeConsole, RawModeOptions, RawScreen,
+};
+use lazy_static::lazy_static;
+use winapi::um::wincon::{ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT};
+
+lazy_static! {
+    // The console backend is global, the tests must not run concurrently
+    static ref CONSOLE: Mutex<()> = Mutex::new(());
+}
+
+// Runs the test against a new fake console, the legacy alternate screen is forced
+fn with_fake_console(test: impl FnOnce(&FakeConsole)) {
+    let _lock = CONSOLE.lock().unwrap_or_else(|e| e.into_inner());
+
+    let console = Arc::new(FakeConsole::new());
+    set_console_backend(Some(console.clone()));
+    force_ansi(false);
+
+    test(&console);
+
+    set_console_backend(None);
+}
+
+#[test]
+fn raw_mode_clears_and_restores_the_input_mode() {
+    with_fake_console(|console| {
+        let original = console.input_mode();
+
+        let raw = RawScreen::into_raw_mode().unwrap();
+        assert_eq!(
+            console.input_mode() & (ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT),
+            0
+        );
+
+        drop(raw);
+        assert_eq!(console.input_mode(), original);
+    });
+}
+
+#[test]
+fn cbreak_mode_keeps_the_processed_input() {
+    with_fake_console(|console| {
+        let _cbreak = RawScreen::into_cbreak_mode().unwrap();
+
+        let mode = console.input_mode();
+        assert_eq!(mode & (ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT), 0);
+        assert_ne!(mode & ENABLE_PROCESSED_INPUT, 0);
+    });
+}
+
+#[test]
+fn raw_mode_is_reference_counted() {
+    with_fake_console(|console| {
+        let original = console.input_mode();
+
+        let first = RawScreen::into_raw_mode().unwrap();
+        let second = RawScreen::into_raw_mode().unwrap();
+
+        drop(first);
+        assert_eq!(console.input_mode() & ENABLE_LINE_INPUT, 0);
+
+        drop(second);
+        assert_eq!(console.input_mode(), original);
+    });
+}
+
+#[test]
+fn raw_mode_flushes_the_input() {
+    with_fake_console(|console| {
+        let raw = RawScreen::into_raw_mode_with(RawModeOptions::raw().flush_input(true)).unwrap();
+        assert_eq!(console.input_flushes(), 1);
+
+        drop(raw);
+        assert_eq!(console.input_flushes(), 2);
+    });
+}
+
+#[test]
+fn alternate_screen_switches_the_screen_buffers() {
+    with_fake_console(|console| {
+        let alternate = AlternateScreen::to_alternate(false).unwrap();
+        assert!(!console.is_main_screen_buffer_active());
+        assert_eq!(console.screen_buffers(), 2);
+        // The alternate screen buffer has no scrollback
+        assert_eq!(console.active_screen_buffer_size(), (120, 30));
+
+        drop(alternate);
+        assert!(console.is_main_screen_buffer_active());
+        assert_eq!(console.active_screen_buffer_size(), (120, 9001));
+    });
+}
+
+#[test]
+fn nested_alternate_screen_shares_the_screen_buffer() {
+    with_fake_console(|console| {
+        let outer = AlternateScreen::to_alternate(false).unwrap();
+        let inner = AlternateScreen::to_alternate(false).unwrap();
+        assert_eq!(console.screen_buffers(), 2);
+
+        drop(inner);
+        assert!(!console.is_main_screen_buffer_active());
+
+        drop(outer);
+        assert!(console.is_main_screen_buffer_active());
+    });
+}
+
+#[test]
+fn alternate_screen_with_raw_mode_restores_both() {
+    with_fake_console(|console| {
+        let original = console.input_mode();
+
+        let alternate = AlternateScreen::to_alternate(true).unwrap();
+        assert!(!console.is_main_screen_buffer_active());
+        assert_eq!(console.input_mode() & ENABLE_LINE_INPUT, 0);
+
+        drop(alternate);
+        assert!(console.is_main_screen_buffer_active());
+        assert_eq!(console.input_mode(), original);
+    });
+}
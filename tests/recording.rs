@@ -0,0 +1,236 @@
+//! The alternate screen & raw mode event log of the `RecordingScreen`.
+#![cfg(feature = "recording")]
+
+use std::env;
+use std::io::Write;
+
+use crossterm_screen::{
+    AlternateScreen, RecordedEvent, RecordingScreen, ScreenBufferId, ScreenBufferManager,
+    ScreenError, VirtualScreen,
+};
+
+// Creates a recording screen of an xterm, the environment is shared by the recording
+// screens (there's one at a time)
+fn recording_screen() -> RecordingScreen {
+    let screen = RecordingScreen::new();
+    env::set_var("TERM", "xterm");
+    env::remove_var("CROSSTERM_NO_ALT_SCREEN");
+    screen
+}
+
+// Checks whether the event is a write containing the sequence
+fn writes(event: &RecordedEvent, sequence: &str) -> bool {
+    match event {
+        RecordedEvent::Write(text) => text.contains(sequence),
+        _ => false,
+    }
+}
+
+#[test]
+fn enter_and_leave_are_recorded() {
+    let mut screen = recording_screen();
+
+    screen.to_alternate(false).unwrap();
+    assert!(screen.is_alternate_screen());
+
+    screen.to_main().unwrap();
+    assert!(!screen.is_alternate_screen());
+
+    let events = screen.events();
+    assert_eq!(events.len(), 4);
+    assert!(writes(&events[0], "\x1B[?1049h"));
+    assert_eq!(events[1], RecordedEvent::AlternateScreenEntered);
+    assert!(writes(&events[2], "\x1B[?1049l"));
+    assert_eq!(events[3], RecordedEvent::AlternateScreenLeft);
+}
+
+#[test]
+fn raw_mode_is_disabled_before_the_main_screen_is_shown() {
+    let mut screen = recording_screen();
+
+    screen.to_alternate(true).unwrap();
+    assert!(screen.is_raw_mode_enabled());
+
+    screen.to_main().unwrap();
+    assert!(!screen.is_raw_mode_enabled());
+
+    let events = screen.events();
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[1], RecordedEvent::AlternateScreenEntered);
+    assert_eq!(events[2], RecordedEvent::RawModeEnabled);
+    assert_eq!(events[3], RecordedEvent::RawModeDisabled);
+    assert!(writes(&events[4], "\x1B[?1049l"));
+    assert_eq!(events[5], RecordedEvent::AlternateScreenLeft);
+}
+
+#[test]
+fn nested_changes_are_recorded_once() {
+    let mut screen = recording_screen();
+
+    screen.to_alternate(false).unwrap();
+    screen.to_alternate(true).unwrap();
+    screen.enable_raw_mode().unwrap();
+
+    // The inner alternate screen is left, the outer one is still shown
+    screen.to_main().unwrap();
+    assert!(screen.is_alternate_screen());
+    assert!(screen.is_raw_mode_enabled());
+
+    screen.to_main().unwrap();
+    assert!(!screen.is_alternate_screen());
+
+    screen.disable_raw_mode().unwrap();
+    assert!(!screen.is_raw_mode_enabled());
+
+    let events = screen.events();
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[1], RecordedEvent::AlternateScreenEntered);
+    assert_eq!(events[2], RecordedEvent::RawModeEnabled);
+    assert!(writes(&events[3], "\x1B[?1049l"));
+    assert_eq!(events[4], RecordedEvent::AlternateScreenLeft);
+    assert_eq!(events[5], RecordedEvent::RawModeDisabled);
+}
+
+#[test]
+fn builder_features_are_recorded() {
+    let screen = recording_screen();
+
+    let alternate = AlternateScreen::builder()
+        .hide_cursor(true)
+        .bracketed_paste(true)
+        .enter()
+        .unwrap();
+    drop(alternate);
+
+    let output = screen.output();
+    let enter = output.find("\x1B[?1049h").unwrap();
+    let hide_cursor = output.find("\x1B[?25l").unwrap();
+    let show_cursor = output.find("\x1B[?25h").unwrap();
+    let leave = output.rfind("\x1B[?1049l").unwrap();
+    assert!(enter < hide_cursor && hide_cursor < show_cursor && show_cursor < leave);
+    assert!(output.contains("\x1B[?2004h"));
+}
+
+#[test]
+fn screen_terminals_use_the_composed_sequences() {
+    let mut screen = recording_screen();
+    env::set_var("TERM", "screen");
+
+    screen.to_alternate(false).unwrap();
+    screen.to_main().unwrap();
+
+    let events = screen.events();
+    assert!(writes(&events[0], "\x1B[?1048h\x1B[?47h"));
+    assert!(writes(&events[2], "\x1B[?47l\x1B[?1048l"));
+}
+
+#[test]
+fn dumb_terminals_stay_on_the_main_screen() {
+    let mut screen = recording_screen();
+    env::set_var("TERM", "dumb");
+
+    screen.to_alternate(true).unwrap();
+    assert!(!screen.is_alternate_screen());
+    screen.to_main().unwrap();
+
+    // The raw mode is not affected
+    assert_eq!(
+        screen.events(),
+        vec![
+            RecordedEvent::RawModeEnabled,
+            RecordedEvent::RawModeDisabled
+        ]
+    );
+}
+
+#[test]
+fn disabled_alternate_screen_is_not_entered() {
+    let mut screen = recording_screen();
+    env::set_var("CROSSTERM_NO_ALT_SCREEN", "1");
+
+    screen.to_alternate(false).unwrap();
+    assert!(!screen.is_alternate_screen());
+    screen.to_main().unwrap();
+
+    assert!(screen.events().is_empty());
+}
+
+#[test]
+fn writes_are_recorded() {
+    let mut screen = recording_screen();
+
+    write!(screen, "Hello").unwrap();
+    screen.write_all(b"").unwrap();
+    screen.to_alternate(false).unwrap();
+    write!(screen, "World").unwrap();
+
+    assert_eq!(
+        screen.events()[0],
+        RecordedEvent::Write("Hello".to_string())
+    );
+    assert!(screen.output().starts_with("Hello\x1B[?1049h"));
+    assert!(screen.output().ends_with("World"));
+}
+
+#[test]
+fn split_characters_are_recorded_whole() {
+    let mut screen = recording_screen();
+    let bytes = "日本".as_bytes();
+
+    screen.write_all(&bytes[..2]).unwrap();
+    assert!(screen.events().is_empty());
+
+    screen.write_all(&bytes[2..4]).unwrap();
+    screen.write_all(&bytes[4..]).unwrap();
+
+    assert_eq!(screen.output(), "日本");
+}
+
+#[test]
+fn clear_keeps_the_screen_state() {
+    let mut screen = recording_screen();
+
+    screen.to_alternate(true).unwrap();
+    screen.clear();
+
+    assert!(screen.events().is_empty());
+    assert!(screen.is_alternate_screen());
+    assert!(screen.is_raw_mode_enabled());
+
+    screen.to_main().unwrap();
+    assert_eq!(screen.events()[0], RecordedEvent::RawModeDisabled);
+}
+
+#[test]
+fn only_captured_frames_are_returned() {
+    let _screen = recording_screen();
+    let alternate = AlternateScreen::to_alternate(false).unwrap();
+
+    let mut captured = VirtualScreen::new(5, 1);
+    captured.capture_frames(true);
+    captured.print(0, 0, "shown");
+    captured.flush(&mut Vec::new()).unwrap();
+
+    let mut other = VirtualScreen::new(5, 1);
+    other.print(0, 0, "other");
+    other.flush(&mut Vec::new()).unwrap();
+
+    assert_eq!(alternate.capture().unwrap().lines(), ["shown"]);
+}
+
+#[test]
+fn buffers_do_not_take_over_the_alternate_screen() {
+    let mut screen = recording_screen();
+    screen.to_alternate(false).unwrap();
+
+    let mut manager = ScreenBufferManager::new();
+    let buffer = manager.create().unwrap();
+
+    let error = manager.activate(buffer).unwrap_err();
+    assert_eq!(
+        ScreenError::from_error_kind(&error),
+        Some(ScreenError::AlternateScreenInUse)
+    );
+    assert_eq!(manager.active(), ScreenBufferId::MAIN);
+    assert_eq!(screen.events().len(), 2);
+}
@@ -0,0 +1,121 @@
+//! The diff-based flushing of the `VirtualScreen`.
+
+use crossterm_screen::VirtualScreen;
+
+// Flushes the virtual screen and returns what was written
+fn flush(screen: &mut VirtualScreen) -> String {
+    let mut output = Vec::new();
+    screen.flush(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn first_frame_is_written_as_a_whole() {
+    let mut screen = VirtualScreen::new(3, 2);
+    screen.print(0, 0, "ab");
+
+    assert_eq!(flush(&mut screen), "\x1B[1;1Hab \x1B[2;1H   ");
+}
+
+#[test]
+fn unchanged_frame_writes_nothing() {
+    let mut screen = VirtualScreen::new(3, 2);
+    screen.print(0, 0, "ab");
+    flush(&mut screen);
+
+    assert_eq!(flush(&mut screen), "");
+
+    // The same frame drawn again
+    screen.clear();
+    screen.print(0, 0, "ab");
+    assert_eq!(flush(&mut screen), "");
+}
+
+#[test]
+fn single_cell_change_writes_the_cell_only() {
+    let mut screen = VirtualScreen::new(5, 2);
+    flush(&mut screen);
+
+    screen.set(2, 1, 'x');
+    assert_eq!(flush(&mut screen), "\x1B[2;3Hx");
+
+    screen.set(2, 1, ' ');
+    assert_eq!(flush(&mut screen), "\x1B[2;3H ");
+}
+
+#[test]
+fn short_gaps_are_written_again() {
+    let mut screen = VirtualScreen::new(20, 1);
+    flush(&mut screen);
+
+    // The one cell gap is shorter than the cursor movement
+    screen.set(0, 0, 'a');
+    screen.set(2, 0, 'b');
+    assert_eq!(flush(&mut screen), "\x1B[1;1Ha b");
+
+    // The long gap is skipped
+    screen.set(0, 0, 'c');
+    screen.set(15, 0, 'd');
+    assert_eq!(flush(&mut screen), "\x1B[1;1Hc\x1B[1;16Hd");
+}
+
+#[test]
+fn wide_characters_take_two_cells() {
+    let mut screen = VirtualScreen::new(5, 1);
+    screen.print(0, 0, "a日b");
+
+    assert_eq!(screen.get(1, 0), Some('日'));
+    assert_eq!(screen.get(2, 0), None);
+    assert_eq!(screen.get(3, 0), Some('b'));
+    assert_eq!(flush(&mut screen), "\x1B[1;1Ha日b ");
+
+    // The gap is measured in columns
+    screen.set(0, 0, 'c');
+    screen.set(3, 0, 'd');
+    assert_eq!(flush(&mut screen), "\x1B[1;1Hc日d");
+}
+
+#[test]
+fn wide_characters_are_not_split() {
+    let mut screen = VirtualScreen::new(3, 1);
+
+    // The wide characters don't fit in the last column
+    screen.print(2, 0, "日");
+    assert_eq!(screen.get(2, 0), Some(' '));
+    screen.set(2, 0, '本');
+    assert_eq!(screen.get(2, 0), Some(' '));
+
+    screen.print(0, 0, "日");
+    flush(&mut screen);
+
+    // Overwriting half of a wide character clears the other half
+    screen.set(1, 0, 'x');
+    assert_eq!(screen.get(0, 0), Some(' '));
+    assert_eq!(flush(&mut screen), "\x1B[1;1H x");
+}
+
+#[test]
+fn resize_writes_the_whole_frame() {
+    let mut screen = VirtualScreen::new(3, 1);
+    screen.print(0, 0, "abc");
+    flush(&mut screen);
+
+    screen.resize(2, 2);
+    assert_eq!(screen.size(), (2, 2));
+    assert_eq!(screen.get(0, 0), Some(' '));
+    assert_eq!(screen.get(2, 0), None);
+
+    screen.print(0, 1, "xyz");
+    assert_eq!(flush(&mut screen), "\x1B[1;1H  \x1B[2;1Hxy");
+    assert_eq!(flush(&mut screen), "");
+}
+
+#[test]
+fn invalidate_writes_the_whole_frame() {
+    let mut screen = VirtualScreen::new(2, 1);
+    screen.print(0, 0, "ab");
+    flush(&mut screen);
+
+    screen.invalidate();
+    assert_eq!(flush(&mut screen), "\x1B[1;1Hab");
+}